@@ -0,0 +1,114 @@
+"""Python bindings of the SCB Open API SDK.
+
+Responses are dicts with the field names of the SCB API. Errors of the SDK are raised
+as subclasses of SCBException, invalid requests as ValueError.
+"""
+
+import asyncio
+import functools
+
+from ._corescbsdk import (
+    AuthorizationError,
+    BillPaymentTransactionIter,
+    CircuitOpenError,
+    DuplicateSlipError,
+    HttpRequestError,
+    InvalidResponseError,
+    QRCodeRequest,
+    QRCodeRequestBuilder,
+    RateLimitedError,
+    SCBClient,
+    SCBError,
+    SCBException,
+    ServerError,
+    StorageError,
+)
+
+__all__ = [
+    "AsyncSCBClient",
+    "AuthorizationError",
+    "BillPaymentTransactionIter",
+    "CircuitOpenError",
+    "DuplicateSlipError",
+    "HttpRequestError",
+    "InvalidResponseError",
+    "QRCodeRequest",
+    "QRCodeRequestBuilder",
+    "RateLimitedError",
+    "SCBClient",
+    "SCBError",
+    "SCBException",
+    "ServerError",
+    "StorageError",
+]
+
+
+class AsyncSCBClient:
+    """asyncio client with the methods of SCBClient as coroutines.
+
+    Requests run in the default executor of the event loop, one at a time per client
+    so the access token is requested once.
+    """
+
+    def __init__(self, application_name, application_key, secret_key):
+        self._client = SCBClient(application_name, application_key, secret_key)
+        # Created in _call, before Python 3.10 a lock binds to the loop current at creation
+        self._lock = None
+
+    def set_ref3_prefix(self, ref3_prefix):
+        self._client.set_ref3_prefix(ref3_prefix)
+
+    def set_slip_registry_sqlite(self, path):
+        self._client.set_slip_registry_sqlite(path)
+
+    async def _call(self, method, *args, **kwargs):
+        if self._lock is None:
+            self._lock = asyncio.Lock()
+        async with self._lock:
+            loop = asyncio.get_running_loop()
+            return await loop.run_in_executor(
+                None, functools.partial(method, *args, **kwargs)
+            )
+
+    async def get_access_token(self):
+        return await self._call(self._client.get_access_token)
+
+    async def qr_code_create(self, request):
+        return await self._call(self._client.qr_code_create, request)
+
+    async def get_slip_verification_qr30(self, trans_ref, sending_bank, order_id=None):
+        return await self._call(
+            self._client.get_slip_verification_qr30, trans_ref, sending_bank, order_id
+        )
+
+    async def query_bill_payment_transaction(self, transaction_date, **params):
+        return await self._call(
+            self._client.query_bill_payment_transaction, transaction_date, **params
+        )
+
+    async def query_bill_payment_transaction_iter(
+        self, biller_ids, references1, from_date, to_date, concurrency=4
+    ):
+        """Async generator of the transactions of a date range.
+
+        A failed day raises the exception of its error and ends the generator.
+        """
+        transactions = await self._call(
+            self._client.query_bill_payment_transaction_iter,
+            biller_ids,
+            references1,
+            from_date,
+            to_date,
+            concurrency,
+        )
+        loop = asyncio.get_running_loop()
+        while True:
+            transaction = await loop.run_in_executor(None, next, transactions, None)
+            if transaction is None:
+                return
+            yield transaction
+
+    async def reconcile_bill_payments(self, biller_id, from_date, to_date, expected):
+        return await self._call(
+            self._client.reconcile_bill_payments, biller_id, from_date, to_date, expected
+        )
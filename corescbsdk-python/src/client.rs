@@ -0,0 +1,218 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::NaiveDate;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use validator::Validate;
+
+use corescbsdk::entities::bank_code::BankCode;
+use corescbsdk::entities::bill_pay::{BillPaymentInquiryRequest, EVENT_CODE_QR30_C_SCAN_B};
+use corescbsdk::entities::reconciliation::ExpectedPayment;
+use corescbsdk::errors::scb_error::SCBAPIError;
+use corescbsdk::frameworks::apis::blocking;
+use corescbsdk::frameworks::stores::sqlite::SqliteSlipRegistry;
+
+use crate::convert::{from_py, to_py};
+use crate::errors::{inquiry_err, to_py_err, validation_err};
+use crate::qr_code::QRCodeRequest;
+
+#[allow(clippy::too_many_arguments)]
+fn inquiry_request(
+    transaction_date: String,
+    event_code: Option<String>,
+    biller_id: Option<String>,
+    reference1: Option<String>,
+    reference2: Option<String>,
+    partner_transaction_id: Option<String>,
+    amount: Option<f64>,
+) -> PyResult<BillPaymentInquiryRequest> {
+    let request = BillPaymentInquiryRequest {
+        event_code: event_code.unwrap_or_else(|| EVENT_CODE_QR30_C_SCAN_B.to_string()),
+        transaction_date,
+        biller_id,
+        reference1,
+        reference2,
+        partner_transaction_id,
+        amount,
+    };
+    request
+        .validate()
+        .map_err(|e| validation_err("inquiry request", e))?;
+    Ok(request)
+}
+
+fn parse_date(date: &str) -> PyResult<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| PyValueError::new_err(format!("invalid date {}, expected YYYY-MM-DD", date)))
+}
+
+// Transactions of a date range as dicts. A failed day raises the exception of its error,
+// calling next() again continues with the other days.
+#[pyclass]
+pub struct BillPaymentTransactionIter {
+    // The stream is Send but not Sync
+    iter: Mutex<blocking::BillPaymentTransactionIter>,
+}
+
+#[pymethods]
+impl BillPaymentTransactionIter {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+        let item = py.allow_threads(|| self.iter.lock().unwrap().next());
+        match item {
+            Some(Ok(transaction)) => to_py(py, &transaction).map(Some),
+            Some(Err(e)) => Err(inquiry_err(e)),
+            None => Ok(None),
+        }
+    }
+}
+
+// Synchronous client, releases the GIL while waiting for SCB.
+// Calls on one client must not overlap, AsyncSCBClient in python/corescbsdk serializes them.
+#[pyclass]
+pub struct SCBClient {
+    client: blocking::SCBClientAPI,
+}
+
+#[pymethods]
+impl SCBClient {
+    #[new]
+    fn new(application_name: String, application_key: String, secret_key: String) -> Self {
+        SCBClient {
+            client: blocking::SCBClientAPI::new(&application_name, &application_key, &secret_key),
+        }
+    }
+
+    fn set_ref3_prefix(&mut self, ref3_prefix: &str) {
+        self.client.set_ref3_prefix(ref3_prefix);
+    }
+
+    // SQLite database the slips verified with an order_id are claimed in, created when missing
+    fn set_slip_registry_sqlite(&mut self, path: &str) -> PyResult<()> {
+        let registry =
+            SqliteSlipRegistry::open(path).map_err(|e| to_py_err(SCBAPIError::from(e)))?;
+        self.client.set_slip_registry(Arc::new(registry));
+        Ok(())
+    }
+
+    fn get_access_token(&mut self, py: Python<'_>) -> PyResult<PyObject> {
+        let token = py
+            .allow_threads(|| self.client.get_access_token())
+            .map_err(to_py_err)?;
+        to_py(py, &token)
+    }
+
+    fn qr_code_create(&mut self, py: Python<'_>, request: &QRCodeRequest) -> PyResult<PyObject> {
+        let response = py
+            .allow_threads(|| self.client.qr_code_create(&request.request))
+            .map_err(to_py_err)?;
+        to_py(py, &response)
+    }
+
+    // `sending_bank` is the 3 digit bank code, e.g. 014. With `order_id` the slip is claimed
+    // for the order and reuse raises DuplicateSlipError, StorageError without a slip registry.
+    #[pyo3(signature = (trans_ref, sending_bank, order_id=None))]
+    fn get_slip_verification_qr30(
+        &mut self,
+        py: Python<'_>,
+        trans_ref: String,
+        sending_bank: &str,
+        order_id: Option<String>,
+    ) -> PyResult<PyObject> {
+        let sending_bank = BankCode::from_code(sending_bank);
+        let slip = py
+            .allow_threads(|| match &order_id {
+                Some(order_id) => {
+                    self.client
+                        .get_slip_verification_for_order(&trans_ref, &sending_bank, order_id)
+                }
+                None => self
+                    .client
+                    .get_slip_verification_qr30(&trans_ref, &sending_bank),
+            })
+            .map_err(to_py_err)?;
+        to_py(py, &slip)
+    }
+
+    // C scan B inquiry by default, pass event_code="00300104" for B scan C
+    #[pyo3(signature = (transaction_date, event_code=None, biller_id=None, reference1=None, reference2=None, partner_transaction_id=None, amount=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn query_bill_payment_transaction(
+        &mut self,
+        py: Python<'_>,
+        transaction_date: String,
+        event_code: Option<String>,
+        biller_id: Option<String>,
+        reference1: Option<String>,
+        reference2: Option<String>,
+        partner_transaction_id: Option<String>,
+        amount: Option<f64>,
+    ) -> PyResult<PyObject> {
+        let request = inquiry_request(
+            transaction_date,
+            event_code,
+            biller_id,
+            reference1,
+            reference2,
+            partner_transaction_id,
+            amount,
+        )?;
+        let transactions = py
+            .allow_threads(|| self.client.query_bill_payment_transaction(&request))
+            .map_err(to_py_err)?;
+        to_py(py, &transactions)
+    }
+
+    // One C scan B inquiry per day from `from_date` to `to_date`, biller and reference1,
+    // dates as YYYY-MM-DD
+    #[pyo3(signature = (biller_ids, references1, from_date, to_date, concurrency=4))]
+    fn query_bill_payment_transaction_iter(
+        &mut self,
+        py: Python<'_>,
+        biller_ids: Vec<String>,
+        references1: Vec<String>,
+        from_date: &str,
+        to_date: &str,
+        concurrency: usize,
+    ) -> PyResult<BillPaymentTransactionIter> {
+        let (from, to) = (parse_date(from_date)?, parse_date(to_date)?);
+        let iter = py
+            .allow_threads(|| {
+                self.client.query_bill_payment_transaction_iter(
+                    &biller_ids,
+                    &references1,
+                    from,
+                    to,
+                    concurrency,
+                )
+            })
+            .map_err(to_py_err)?;
+        Ok(BillPaymentTransactionIter {
+            iter: Mutex::new(iter),
+        })
+    }
+
+    // `expected` is a list of dicts with reference1, amount e.g. "100.00" and optional reference2.
+    // Returns the report with matched, missing, unexpected and amount_mismatch.
+    fn reconcile_bill_payments(
+        &mut self,
+        py: Python<'_>,
+        biller_id: &str,
+        from_date: &str,
+        to_date: &str,
+        expected: &Bound<'_, PyAny>,
+    ) -> PyResult<PyObject> {
+        let (from, to) = (parse_date(from_date)?, parse_date(to_date)?);
+        let expected: Vec<ExpectedPayment> = from_py(expected)?;
+        let report = py
+            .allow_threads(|| {
+                self.client
+                    .reconcile_bill_payments(biller_id, from, to, &expected)
+            })
+            .map_err(to_py_err)?;
+        to_py(py, &report)
+    }
+}
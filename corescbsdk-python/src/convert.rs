@@ -0,0 +1,115 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+// Responses are returned to Python as dicts and lists with the field names of the SCB API
+pub(crate) fn to_py<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let value = serde_json::to_value(value).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    value_to_py(py, &value)
+}
+
+fn value_to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    let object = match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_pyobject(py)?.to_owned().into_any().unbind(),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into_pyobject(py)?.into_any().unbind(),
+            None => n.as_f64().into_pyobject(py)?.into_any().unbind(),
+        },
+        Value::String(s) => s.into_pyobject(py)?.into_any().unbind(),
+        Value::Array(values) => {
+            let list = PyList::empty(py);
+            for value in values {
+                list.append(value_to_py(py, value)?)?;
+            }
+            list.into_any().unbind()
+        }
+        Value::Object(fields) => {
+            let dict = PyDict::new(py);
+            for (key, value) in fields {
+                dict.set_item(key, value_to_py(py, value)?)?;
+            }
+            dict.into_any().unbind()
+        }
+    };
+    Ok(object)
+}
+
+// Requests are taken from Python as dicts and lists with the field names of the SDK entities
+pub(crate) fn from_py<T: DeserializeOwned>(object: &Bound<'_, PyAny>) -> PyResult<T> {
+    let value = py_to_value(object)?;
+    serde_json::from_value(value).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+fn py_to_value(object: &Bound<'_, PyAny>) -> PyResult<Value> {
+    // bool before int, Python bools are ints
+    let value = if object.is_none() {
+        Value::Null
+    } else if object.is_instance_of::<PyBool>() {
+        Value::Bool(object.extract()?)
+    } else if object.is_instance_of::<PyInt>() {
+        Value::from(object.extract::<i64>()?)
+    } else if object.is_instance_of::<PyFloat>() {
+        Value::from(object.extract::<f64>()?)
+    } else if object.is_instance_of::<PyString>() {
+        Value::String(object.extract()?)
+    } else if object.is_instance_of::<PyList>() || object.is_instance_of::<PyTuple>() {
+        Value::Array(
+            object
+                .try_iter()?
+                .map(|item| py_to_value(&item?))
+                .collect::<PyResult<_>>()?,
+        )
+    } else if let Ok(dict) = object.downcast::<PyDict>() {
+        let mut fields = Map::new();
+        for (key, value) in dict {
+            fields.insert(key.extract()?, py_to_value(&value)?);
+        }
+        Value::Object(fields)
+    } else {
+        return Err(PyValueError::new_err(format!(
+            "unsupported value {}",
+            object.repr()?
+        )));
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_to_py() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let value = json!({"amount": 100.5, "count": 2, "ref2": null, "items": ["A", true]});
+            let object = to_py(py, &value).unwrap();
+            let dict = object.downcast_bound::<PyDict>(py).unwrap();
+            let get = |key: &str| dict.get_item(key).unwrap().unwrap();
+            assert_eq!(get("amount").extract::<f64>().unwrap(), 100.5);
+            assert_eq!(get("count").extract::<i64>().unwrap(), 2);
+            assert!(get("ref2").is_none());
+            assert_eq!(get("items").repr().unwrap().to_string(), "['A', True]");
+        });
+    }
+
+    #[test]
+    fn test_from_py() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let value = json!({"amount": 100.5, "count": 2, "ref2": null, "items": ["A", true]});
+            let object = to_py(py, &value).unwrap();
+            assert_eq!(from_py::<Value>(object.bind(py)).unwrap(), value);
+
+            let object = ("ORDER1", 1).into_pyobject(py).unwrap();
+            assert_eq!(from_py::<Value>(&object).unwrap(), json!(["ORDER1", 1]));
+            assert!(from_py::<String>(&object).is_err());
+        });
+    }
+}
@@ -0,0 +1,116 @@
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use validator::ValidationErrors;
+
+use corescbsdk::entities::bill_pay::BillPaymentInquiryError;
+use corescbsdk::errors::scb_error::SCBAPIError;
+
+// One exception per SCBAPIError variant, all subclasses of SCBException
+create_exception!(corescbsdk, SCBException, PyException);
+create_exception!(corescbsdk, HttpRequestError, SCBException);
+create_exception!(corescbsdk, SCBError, SCBException);
+create_exception!(corescbsdk, AuthorizationError, SCBException);
+create_exception!(corescbsdk, DuplicateSlipError, SCBException);
+create_exception!(corescbsdk, StorageError, SCBException);
+create_exception!(corescbsdk, RateLimitedError, SCBException);
+create_exception!(corescbsdk, CircuitOpenError, SCBException);
+create_exception!(corescbsdk, ServerError, SCBException);
+create_exception!(corescbsdk, InvalidResponseError, SCBException);
+
+// Errors of the request pipeline carry the seconds to wait as second argument
+pub(crate) fn to_py_err(error: SCBAPIError) -> PyErr {
+    let message = error.to_string();
+    scb_err(error, message)
+}
+
+// Failed day of a date range, raised as the exception of its SCBAPIError with the day in the message
+pub(crate) fn inquiry_err(error: BillPaymentInquiryError) -> PyErr {
+    let message = error.to_string();
+    scb_err(error.error, message)
+}
+
+fn scb_err(error: SCBAPIError, message: String) -> PyErr {
+    match error {
+        SCBAPIError::HttpRequestError(_) => HttpRequestError::new_err(message),
+        SCBAPIError::SCBError(_) => SCBError::new_err(message),
+        SCBAPIError::AuthorizationError(_) => AuthorizationError::new_err(message),
+        SCBAPIError::DuplicateSlip { .. } => DuplicateSlipError::new_err(message),
+        SCBAPIError::StorageError(_) => StorageError::new_err(message),
+        SCBAPIError::RateLimited { retry_after, .. } => {
+            RateLimitedError::new_err((message, retry_after.as_secs_f64()))
+        }
+        SCBAPIError::CircuitOpen { retry_after, .. } => {
+            CircuitOpenError::new_err((message, retry_after.as_secs_f64()))
+        }
+        SCBAPIError::ServerError { .. } => ServerError::new_err(message),
+        SCBAPIError::InvalidResponse(_) => InvalidResponseError::new_err(message),
+    }
+}
+
+pub(crate) fn validation_err(what: &str, errors: ValidationErrors) -> PyErr {
+    PyValueError::new_err(format!("invalid {}: {}", what, errors))
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("SCBException", py.get_type::<SCBException>())?;
+    m.add("HttpRequestError", py.get_type::<HttpRequestError>())?;
+    m.add("SCBError", py.get_type::<SCBError>())?;
+    m.add("AuthorizationError", py.get_type::<AuthorizationError>())?;
+    m.add("DuplicateSlipError", py.get_type::<DuplicateSlipError>())?;
+    m.add("StorageError", py.get_type::<StorageError>())?;
+    m.add("RateLimitedError", py.get_type::<RateLimitedError>())?;
+    m.add("CircuitOpenError", py.get_type::<CircuitOpenError>())?;
+    m.add("ServerError", py.get_type::<ServerError>())?;
+    m.add(
+        "InvalidResponseError",
+        py.get_type::<InvalidResponseError>(),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_error_mapping() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let error = to_py_err(SCBAPIError::RateLimited {
+                endpoint: "inquiry".to_string(),
+                retry_after: Duration::from_millis(1500),
+            });
+            assert!(error.is_instance_of::<RateLimitedError>(py));
+            assert!(error.is_instance_of::<SCBException>(py));
+            let retry_after: f64 = error
+                .value(py)
+                .getattr("args")
+                .unwrap()
+                .get_item(1)
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(retry_after, 1.5);
+
+            let error = to_py_err(SCBAPIError::SCBError("Invalid".to_string()));
+            assert!(error.is_instance_of::<SCBError>(py));
+            assert!(!error.is_instance_of::<HttpRequestError>(py));
+
+            let error = inquiry_err(BillPaymentInquiryError {
+                transaction_date: "2024-01-31".to_string(),
+                biller_id: Some("123456789012345".to_string()),
+                reference1: Some("ORDER1".to_string()),
+                error: SCBAPIError::ServerError {
+                    status: 503,
+                    message: "Service Unavailable".to_string(),
+                },
+            });
+            assert!(error.is_instance_of::<ServerError>(py));
+            assert!(error.value(py).to_string().contains("2024-01-31"));
+        });
+    }
+}
@@ -0,0 +1,18 @@
+use pyo3::prelude::*;
+
+mod client;
+mod convert;
+mod errors;
+mod qr_code;
+
+// Native module, re-exported by python/corescbsdk/__init__.py
+#[pymodule]
+#[pyo3(name = "_corescbsdk")]
+fn corescbsdk_python(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<client::SCBClient>()?;
+    m.add_class::<client::BillPaymentTransactionIter>()?;
+    m.add_class::<qr_code::QRCodeRequestBuilder>()?;
+    m.add_class::<qr_code::QRCodeRequest>()?;
+    errors::register(m)?;
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use validator::Validate;
+
+use corescbsdk::entities::proxy::ProxyType;
+use corescbsdk::entities::qr_code;
+use corescbsdk::entities::qr_code::QRCodeType;
+
+use crate::convert::to_py;
+use crate::errors::validation_err;
+
+// Validated body of the QR code create API, built with QRCodeRequestBuilder
+#[pyclass(frozen)]
+#[derive(Clone)]
+pub struct QRCodeRequest {
+    pub(crate) request: qr_code::QRCodeRequest,
+}
+
+#[pymethods]
+impl QRCodeRequest {
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.request)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("QRCodeRequest({:?})", self.request)
+    }
+}
+
+// Methods return the builder so calls can be chained
+#[pyclass]
+pub struct QRCodeRequestBuilder {
+    builder: qr_code::QRCodeRequestBuilder,
+}
+
+fn parse_qr_type(qr_type: &str) -> PyResult<QRCodeType> {
+    match qr_type.to_ascii_uppercase().as_str() {
+        "PP" => Ok(QRCodeType::PP),
+        "CS" => Ok(QRCodeType::CS),
+        "PPCS" => Ok(QRCodeType::PPCS),
+        _ => Err(PyValueError::new_err(format!(
+            "invalid QR type {}, expected PP, CS or PPCS",
+            qr_type
+        ))),
+    }
+}
+
+#[pymethods]
+impl QRCodeRequestBuilder {
+    #[new]
+    fn new(qr_type: &str, amount: String) -> PyResult<Self> {
+        Ok(QRCodeRequestBuilder {
+            builder: qr_code::QRCodeRequestBuilder::new(&parse_qr_type(qr_type)?, &amount),
+        })
+    }
+
+    fn for_qr_cs(
+        mut slf: PyRefMut<'_, Self>,
+        invoice: String,
+        merchant_id: String,
+        terminal_id: String,
+    ) -> PyRefMut<'_, Self> {
+        slf.builder.for_qr_cs(&invoice, &merchant_id, &terminal_id);
+        slf
+    }
+
+    // `pp_type` as sent to SCB, e.g. BILLERID
+    fn for_qr_tag30(
+        mut slf: PyRefMut<'_, Self>,
+        pp_type: String,
+        pp_id: String,
+        ref1: String,
+        ref3: String,
+    ) -> PyRefMut<'_, Self> {
+        slf.builder
+            .for_qr_tag30(&ProxyType::from(pp_type), &pp_id, &ref1, &ref3);
+        slf
+    }
+
+    fn add_cs_ext_expiry_time(
+        mut slf: PyRefMut<'_, Self>,
+        expiry_time: String,
+    ) -> PyRefMut<'_, Self> {
+        slf.builder.add_cs_ext_expiry_time(&expiry_time);
+        slf
+    }
+
+    fn add_cs_note(mut slf: PyRefMut<'_, Self>, note: String) -> PyRefMut<'_, Self> {
+        slf.builder.add_cs_note(&note);
+        slf
+    }
+
+    fn add_cs_user_defined(
+        mut slf: PyRefMut<'_, Self>,
+        user_defined: String,
+    ) -> PyRefMut<'_, Self> {
+        slf.builder.add_cs_user_defined(&user_defined);
+        slf
+    }
+
+    fn add_ref2(mut slf: PyRefMut<'_, Self>, ref2: String) -> PyRefMut<'_, Self> {
+        slf.builder.add_ref2(&ref2);
+        slf
+    }
+
+    // Raises ValueError for invalid amounts, references or proxy ids
+    fn build(&self) -> PyResult<QRCodeRequest> {
+        let request = self.builder.build().map_err(PyValueError::new_err)?;
+        request
+            .validate()
+            .map_err(|e| validation_err("QR code request", e))?;
+        Ok(QRCodeRequest { request })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_raises_value_error() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let error = QRCodeRequestBuilder::new("XX", "100.00".to_string()).err();
+            assert!(error.unwrap().is_instance_of::<PyValueError>(py));
+
+            let builder = Bound::new(
+                py,
+                QRCodeRequestBuilder::new("PP", "100.00".to_string()).unwrap(),
+            )
+            .unwrap();
+            builder
+                .call_method1(
+                    "for_qr_tag30",
+                    ("BILLERID", "123456789012345", "order-1", "SCB1"),
+                )
+                .unwrap();
+            let error = builder.call_method0("build").unwrap_err();
+            assert!(error.is_instance_of::<PyValueError>(py));
+
+            builder
+                .call_method1(
+                    "for_qr_tag30",
+                    ("BILLERID", "123456789012345", "ORDER1", "SCB1"),
+                )
+                .unwrap();
+            assert!(builder.call_method0("build").is_ok());
+        });
+    }
+}
@@ -0,0 +1,27 @@
+fn main() {
+    #[cfg(feature = "ffi")]
+    generate_header();
+}
+
+// C header of frameworks::ffi, written to OUT_DIR. The copy in include/ is only updated when
+// CORESCBSDK_UPDATE_HEADER is set, a test checks that it is current.
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let header = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("corescbsdk.h");
+    println!("cargo:rerun-if-changed=src/frameworks/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=CORESCBSDK_UPDATE_HEADER");
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+        .expect("Failed to read cbindgen.toml");
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("Failed to generate C header")
+        .write_to_file(&header);
+    if std::env::var_os("CORESCBSDK_UPDATE_HEADER").is_some() {
+        std::fs::copy(&header, format!("{}/include/corescbsdk.h", crate_dir))
+            .expect("Failed to copy the C header to include/");
+    }
+}
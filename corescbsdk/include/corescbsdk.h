@@ -0,0 +1,114 @@
+#ifndef CORESCBSDK_H
+#define CORESCBSDK_H
+
+/* Generated by cbindgen from src/frameworks/ffi.rs, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Result of the library calls, details in scb_last_error_message
+typedef enum SCBErrorCode {
+  SCB_ERROR_CODE_OK = 0,
+  // Null pointer, invalid UTF-8, invalid JSON or a request failing validation
+  SCB_ERROR_CODE_INVALID_ARGUMENT = 1,
+  SCB_ERROR_CODE_HTTP_REQUEST = 2,
+  // Error status returned by SCB
+  SCB_ERROR_CODE_SCB = 3,
+  SCB_ERROR_CODE_AUTHORIZATION = 4,
+  SCB_ERROR_CODE_DUPLICATE_SLIP = 5,
+  SCB_ERROR_CODE_STORAGE = 6,
+  SCB_ERROR_CODE_RATE_LIMITED = 7,
+  SCB_ERROR_CODE_CIRCUIT_OPEN = 8,
+  // Bug in the library, the client handle should not be used anymore
+  SCB_ERROR_CODE_PANIC = 9,
+  // 5xx status from the SCB gateway
+  SCB_ERROR_CODE_SERVER = 10,
+  // Response which is not an SCB response
+  SCB_ERROR_CODE_INVALID_RESPONSE = 11,
+} SCBErrorCode;
+
+// Opaque client handle, created with scb_client_new and released with scb_client_free.
+// A handle must not be used from several threads at the same time.
+typedef struct SCBClientHandle SCBClientHandle;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Create a client with the credentials of an SCB application.
+// Returns null on invalid arguments, see scb_last_error_message.
+//
+// # Safety
+// The arguments must be null or valid NUL terminated strings.
+struct SCBClientHandle *scb_client_new(const char *application_name,
+                                       const char *application_key,
+                                       const char *secret_key);
+
+// Release a client created with scb_client_new. Null is ignored.
+//
+// # Safety
+// `client` must be null or a handle from scb_client_new which was not freed yet.
+void scb_client_free(struct SCBClientHandle *client);
+
+// Claim the slips verified with an order id in the SQLite database at `path`,
+// created when missing. Several processes may share the database.
+//
+// # Safety
+// `client` must be a live handle and `path` a valid NUL terminated string.
+enum SCBErrorCode scb_client_set_slip_registry_sqlite(struct SCBClientHandle *client,
+                                                      const char *path);
+
+// Create a QR code. `request_json` is the body of the QR code create API,
+// e.g. {"qrType":"PP","amount":"100.00","ppType":"BILLERID","ppId":"...","ref1":"..."}.
+// On success `*out_json` is set to the response data.
+//
+// # Safety
+// `client` must be a live handle, `request_json` a valid NUL terminated string
+// and `out_json` a valid pointer.
+enum SCBErrorCode scb_qr_code_create(struct SCBClientHandle *client,
+                                     const char *request_json,
+                                     char **out_json);
+
+// Verify a slip by its transaction reference and the 3 digit code of the sending bank, e.g. "014".
+// When `order_id` is not null the slip is claimed for the order in the registry set with
+// scb_client_set_slip_registry_sqlite, without a registry the call fails with SCB_ERROR_CODE_STORAGE.
+// On success `*out_json` is set to the slip.
+//
+// # Safety
+// `client` must be a live handle, `trans_ref` and `sending_bank` valid NUL terminated strings,
+// `order_id` null or a valid NUL terminated string and `out_json` a valid pointer.
+enum SCBErrorCode scb_slip_verify(struct SCBClientHandle *client,
+                                  const char *trans_ref,
+                                  const char *sending_bank,
+                                  const char *order_id,
+                                  char **out_json);
+
+// Query bill payment transactions. `request_json` holds the query parameters,
+// e.g. {"eventCode":"00300100","transactionDate":"2024-01-31","billerId":"...","reference1":"..."}.
+// On success `*out_json` is set to a JSON array of transactions.
+//
+// # Safety
+// `client` must be a live handle, `request_json` a valid NUL terminated string
+// and `out_json` a valid pointer.
+enum SCBErrorCode scb_bill_payment_inquiry(struct SCBClientHandle *client,
+                                           const char *request_json,
+                                           char **out_json);
+
+// Copy of the message of the last failed call on this thread, or null.
+// Release it with scb_string_free.
+char *scb_last_error_message(void);
+
+// Release a string returned by the library. Null is ignored.
+//
+// # Safety
+// `value` must be null or a string returned by the library which was not freed yet.
+void scb_string_free(char *value);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CORESCBSDK_H */
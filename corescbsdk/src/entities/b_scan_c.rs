@@ -0,0 +1,294 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::entities::emv::{parse_tlv, verify_crc, EmvError, EmvTlv};
+use crate::entities::validate::{validate_amount, validate_data_type_az09};
+
+// Tag of the CRC data object in an EMVCo QR payload
+const EMV_CRC_TAG: &str = "63";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CustomerQRKind {
+    // One-time numeric payment code shown by the customer's banking app
+    PaymentCode,
+    // EMVCo TLV payload, see data objects
+    Emv(Vec<EmvTlv>),
+}
+
+// QR presented by the customer (My Prompt QR) and scanned by the merchant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomerPresentedQR {
+    // Scanned data, sent as is to SCB
+    pub raw: String,
+    pub kind: CustomerQRKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CustomerQRError {
+    Empty,
+    // Payment code has an unexpected length or characters
+    InvalidPaymentCode,
+    // EMVCo payload is malformed or its CRC is wrong
+    InvalidEmv(EmvError),
+}
+
+impl fmt::Display for CustomerQRError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CustomerQRError::Empty => write!(f, "customer QR is empty"),
+            CustomerQRError::InvalidPaymentCode => write!(f, "invalid customer payment code"),
+            CustomerQRError::InvalidEmv(e) => write!(f, "invalid customer QR: {}", e),
+        }
+    }
+}
+
+impl CustomerPresentedQR {
+    pub fn parse(scanned: &str) -> Result<Self, CustomerQRError> {
+        let raw = scanned.trim();
+        if raw.is_empty() {
+            return Err(CustomerQRError::Empty);
+        }
+
+        if raw.chars().all(|c| c.is_ascii_digit()) && !raw.starts_with("000201") {
+            // One-time payment codes are 16 - 24 digits depending on the issuing bank
+            if !(16..=24).contains(&raw.len()) {
+                return Err(CustomerQRError::InvalidPaymentCode);
+            }
+            return Ok(CustomerPresentedQR {
+                raw: raw.to_string(),
+                kind: CustomerQRKind::PaymentCode,
+            });
+        }
+
+        verify_crc(raw, EMV_CRC_TAG).map_err(CustomerQRError::InvalidEmv)?;
+        let objects = parse_tlv(raw).map_err(CustomerQRError::InvalidEmv)?;
+        Ok(CustomerPresentedQR {
+            raw: raw.to_string(),
+            kind: CustomerQRKind::Emv(objects),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct BScanCPaymentRequest {
+    // Data scanned from the customer's QR
+    #[serde(rename = "qrData")]
+    #[validate(length(min = 1, max = 512))]
+    qr_data: String,
+    // Transaction ID from a partner, used later for inquiry and void
+    // Length: up to 35
+    #[serde(rename = "partnerTransactionId")]
+    #[validate(length(min = 1, max = 35))]
+    partner_transaction_id: String,
+    // Amount of transaction with the length up to 13 characters including "." e.g. 100, 100.00
+    #[serde(rename = "amount")]
+    #[validate(custom(function = "validate_amount"))]
+    amount: String,
+    // Currency code as defined by ISO4217
+    // - 764 (Baht)
+    #[serde(rename = "currencyCode")]
+    currency_code: String,
+    // Biller ID
+    // Length: 15
+    #[serde(rename = "billerId", skip_serializing_if = "Option::is_none")]
+    #[validate(length(max = 15))]
+    biller_id: Option<String>,
+    // Terminal ID of the merchant device scanning the QR
+    #[serde(rename = "terminalId", skip_serializing_if = "Option::is_none")]
+    #[validate(length(max = 8))]
+    terminal_id: Option<String>,
+    // Reference number, up to 20 characters
+    // Data Type: [AZ09] English capital letter and number only.
+    #[serde(rename = "reference1", skip_serializing_if = "Option::is_none")]
+    #[validate(length(max = 20), custom(function = "validate_data_type_az09"))]
+    reference1: Option<String>,
+    // Reference number, up to 20 characters
+    // Data Type: [AZ09] English capital letter and number only.
+    #[serde(rename = "reference2", skip_serializing_if = "Option::is_none")]
+    #[validate(length(max = 20), custom(function = "validate_data_type_az09"))]
+    reference2: Option<String>,
+}
+
+impl BScanCPaymentRequest {
+    pub fn partner_transaction_id(&self) -> &str {
+        &self.partner_transaction_id
+    }
+}
+
+pub struct BScanCPaymentRequestBuilder {
+    request: BScanCPaymentRequest,
+}
+
+impl BScanCPaymentRequestBuilder {
+    pub fn new(
+        customer_qr: &CustomerPresentedQR,
+        partner_transaction_id: &String,
+        amount: &String,
+    ) -> Self {
+        BScanCPaymentRequestBuilder {
+            request: BScanCPaymentRequest {
+                qr_data: customer_qr.raw.to_string(),
+                partner_transaction_id: partner_transaction_id.to_string(),
+                amount: amount.to_string(),
+                currency_code: "764".to_string(),
+                biller_id: None,
+                terminal_id: None,
+                reference1: None,
+                reference2: None,
+            },
+        }
+    }
+    pub fn add_biller_id(&mut self, biller_id: &String) -> &mut Self {
+        self.request.biller_id = Some(biller_id.to_string());
+        self
+    }
+    pub fn add_terminal_id(&mut self, terminal_id: &String) -> &mut Self {
+        self.request.terminal_id = Some(terminal_id.to_string());
+        self
+    }
+    pub fn add_reference1(&mut self, reference1: &String) -> &mut Self {
+        self.request.reference1 = Some(reference1.to_string());
+        self
+    }
+    pub fn add_reference2(&mut self, reference2: &String) -> &mut Self {
+        self.request.reference2 = Some(reference2.to_string());
+        self
+    }
+
+    pub fn build(&self) -> Result<BScanCPaymentRequest, &'static str> {
+        self.request
+            .validate()
+            .map_err(|_| "invalid B scan C payment request")?;
+        Ok(self.request.clone())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum BScanCStatus {
+    // Customer's account was debited
+    Success,
+    // Waiting for the customer to confirm on the banking app
+    Pending,
+    // Payment rejected e.g. insufficient funds or expired payment code
+    Failed,
+    // Payment voided by the partner
+    Voided,
+    // Any status not known by this SDK
+    Unknown(String),
+}
+
+impl From<String> for BScanCStatus {
+    fn from(value: String) -> Self {
+        match value.to_uppercase().as_str() {
+            "SUCCESS" | "PAID" | "APPROVED" => BScanCStatus::Success,
+            "PENDING" | "PROCESSING" => BScanCStatus::Pending,
+            "FAILED" | "DECLINED" | "REJECTED" => BScanCStatus::Failed,
+            "VOIDED" | "VOID" | "CANCELLED" => BScanCStatus::Voided,
+            _ => BScanCStatus::Unknown(value),
+        }
+    }
+}
+
+impl From<BScanCStatus> for String {
+    fn from(value: BScanCStatus) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for BScanCStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BScanCStatus::Success => write!(f, "SUCCESS"),
+            BScanCStatus::Pending => write!(f, "PENDING"),
+            BScanCStatus::Failed => write!(f, "FAILED"),
+            BScanCStatus::Voided => write!(f, "VOIDED"),
+            BScanCStatus::Unknown(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BScanCPaymentResponse {
+    // Transaction ID from a partner
+    #[serde(rename = "partnerTransactionId")]
+    pub partner_transaction_id: String,
+    // Transaction ID from SCB
+    #[serde(rename = "transactionId")]
+    pub transaction_id: Option<String>,
+    // Status of the payment
+    #[serde(rename = "status")]
+    pub status: BScanCStatus,
+    // Transaction Amount
+    #[serde(rename = "amount")]
+    pub amount: Option<String>,
+    // Transaction date and time, ISO 8601
+    #[serde(rename = "transactionDateTime")]
+    pub transaction_date_time: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BScanCVoidRequest {
+    // Transaction ID from a partner of the payment to void
+    #[serde(rename = "partnerTransactionId")]
+    pub partner_transaction_id: String,
+    // Transaction ID from SCB of the payment to void, if known
+    #[serde(rename = "transactionId", skip_serializing_if = "Option::is_none")]
+    pub transaction_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BScanCVoidResponse {
+    // Transaction ID from a partner
+    #[serde(rename = "partnerTransactionId")]
+    pub partner_transaction_id: String,
+    // Status of the payment after void
+    #[serde(rename = "status")]
+    pub status: BScanCStatus,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::emv::crc16_ccitt;
+
+    #[test]
+    fn test_parse_customer_qr() {
+        let qr = CustomerPresentedQR::parse(" 2818123456789012345678 ").unwrap();
+        assert_eq!(qr.kind, CustomerQRKind::PaymentCode);
+        assert_eq!(qr.raw, "2818123456789012345678");
+
+        assert_eq!(
+            CustomerPresentedQR::parse("12345"),
+            Err(CustomerQRError::InvalidPaymentCode)
+        );
+        assert_eq!(CustomerPresentedQR::parse(""), Err(CustomerQRError::Empty));
+
+        let body = "000201010212540510.005802TH6304";
+        let payload = format!("{}{:04X}", body, crc16_ccitt(body.as_bytes()));
+        let qr = CustomerPresentedQR::parse(&payload).unwrap();
+        assert!(matches!(qr.kind, CustomerQRKind::Emv(ref o) if o.len() == 5));
+    }
+
+    #[test]
+    fn test_build_payment_request() {
+        let qr = CustomerPresentedQR::parse("2818123456789012345678").unwrap();
+        let request =
+            BScanCPaymentRequestBuilder::new(&qr, &"ORDER0001".to_string(), &"100.00".to_string())
+                .add_reference1(&"REF1".to_string())
+                .build()
+                .unwrap();
+        assert_eq!(request.partner_transaction_id(), "ORDER0001");
+
+        assert!(BScanCPaymentRequestBuilder::new(
+            &qr,
+            &"ORDER0001".to_string(),
+            &"100.001".to_string()
+        )
+        .build()
+        .is_err());
+    }
+}
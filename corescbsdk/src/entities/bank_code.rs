@@ -0,0 +1,177 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+macro_rules! bank_codes {
+    ($($variant:ident => ($code:literal, $short_name:literal, $swift:literal, $name_en:literal, $name_th:literal),)+) => {
+        // Bank / participant codes of the Thai interbank clearing (ITMX / PromptPay)
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        #[serde(from = "String", into = "String")]
+        pub enum BankCode {
+            $($variant,)+
+            // Any code not known by this SDK, kept as sent by SCB
+            Unknown(String),
+        }
+
+        impl BankCode {
+            pub const ALL: &'static [BankCode] = &[$(BankCode::$variant,)+];
+
+            pub fn from_code(code: &str) -> BankCode {
+                match code.trim() {
+                    $($code => BankCode::$variant,)+
+                    other => BankCode::Unknown(other.to_string()),
+                }
+            }
+
+            // 3 digit code e.g. 014
+            pub fn code(&self) -> &str {
+                match self {
+                    $(BankCode::$variant => $code,)+
+                    BankCode::Unknown(code) => code,
+                }
+            }
+
+            // Abbreviation used in Thai banking apps e.g. KBANK
+            pub fn short_name(&self) -> Option<&'static str> {
+                match self {
+                    $(BankCode::$variant => Some($short_name),)+
+                    BankCode::Unknown(_) => None,
+                }
+            }
+
+            pub fn swift_code(&self) -> Option<&'static str> {
+                match self {
+                    $(BankCode::$variant => Some($swift),)+
+                    BankCode::Unknown(_) => None,
+                }
+            }
+
+            pub fn name_en(&self) -> Option<&'static str> {
+                match self {
+                    $(BankCode::$variant => Some($name_en),)+
+                    BankCode::Unknown(_) => None,
+                }
+            }
+
+            pub fn name_th(&self) -> Option<&'static str> {
+                match self {
+                    $(BankCode::$variant => Some($name_th),)+
+                    BankCode::Unknown(_) => None,
+                }
+            }
+        }
+    };
+}
+
+bank_codes! {
+    BBL => ("002", "BBL", "BKKBTHBK", "Bangkok Bank", "ธนาคารกรุงเทพ"),
+    KBANK => ("004", "KBANK", "KASITHBK", "Kasikornbank", "ธนาคารกสิกรไทย"),
+    KTB => ("006", "KTB", "KRTHTHBK", "Krung Thai Bank", "ธนาคารกรุงไทย"),
+    TTB => ("011", "TTB", "TMBKTHBK", "TMBThanachart Bank", "ธนาคารทหารไทยธนชาต"),
+    SCB => ("014", "SCB", "SICOTHBK", "Siam Commercial Bank", "ธนาคารไทยพาณิชย์"),
+    CITI => ("017", "CITI", "CITITHBX", "Citibank", "ธนาคารซิตี้แบงก์"),
+    SMBC => ("018", "SMBC", "SMBCTHBK", "Sumitomo Mitsui Banking Corporation", "ธนาคารซูมิโตโม มิตซุย แบงกิ้ง คอร์ปอเรชั่น"),
+    SCBT => ("020", "SCBT", "SCBLTHBX", "Standard Chartered Bank (Thai)", "ธนาคารสแตนดาร์ดชาร์เตอร์ด (ไทย)"),
+    CIMBT => ("022", "CIMBT", "UBOBTHBK", "CIMB Thai Bank", "ธนาคารซีไอเอ็มบี ไทย"),
+    UOBT => ("024", "UOBT", "UOVBTHBK", "United Overseas Bank (Thai)", "ธนาคารยูโอบี"),
+    BAY => ("025", "BAY", "AYUDTHBK", "Bank of Ayudhya (Krungsri)", "ธนาคารกรุงศรีอยุธยา"),
+    MEGA => ("026", "MEGA", "ICBCTHBK", "Mega International Commercial Bank", "ธนาคารเมกะ สากลพาณิชย์"),
+    BOA => ("027", "BOA", "BOFATH2X", "Bank of America", "ธนาคารแห่งอเมริกา"),
+    GSB => ("030", "GSB", "GSBATHBK", "Government Savings Bank", "ธนาคารออมสิน"),
+    HSBC => ("031", "HSBC", "HSBCTHBK", "Hongkong and Shanghai Banking Corporation", "ธนาคารฮ่องกงและเซี่ยงไฮ้แบงกิ้งคอร์ปอเรชั่น"),
+    DB => ("032", "DB", "DEUTTHBK", "Deutsche Bank", "ธนาคารดอยซ์แบงก์"),
+    GHB => ("033", "GHB", "GOHUTHB1", "Government Housing Bank", "ธนาคารอาคารสงเคราะห์"),
+    BAAC => ("034", "BAAC", "BAABTHBK", "Bank for Agriculture and Agricultural Cooperatives", "ธนาคารเพื่อการเกษตรและสหกรณ์การเกษตร"),
+    EXIM => ("035", "EXIM", "EXTHTHBK", "Export-Import Bank of Thailand", "ธนาคารเพื่อการส่งออกและนำเข้าแห่งประเทศไทย"),
+    MHCB => ("039", "MHCB", "MHCBTHBK", "Mizuho Bank", "ธนาคารมิซูโฮ"),
+    BNPP => ("045", "BNPP", "BNPATHBK", "BNP Paribas", "ธนาคารบีเอ็นพี พารีบาส์"),
+    BOC => ("052", "BOC", "BKCHTHBK", "Bank of China (Thai)", "ธนาคารแห่งประเทศจีน (ไทย)"),
+    TBANK => ("065", "TBANK", "THBKTHBK", "Thanachart Bank", "ธนาคารธนชาต"),
+    IBANK => ("066", "IBANK", "TIBTTHBK", "Islamic Bank of Thailand", "ธนาคารอิสลามแห่งประเทศไทย"),
+    TISCO => ("067", "TISCO", "TFPCTHB1", "TISCO Bank", "ธนาคารทิสโก้"),
+    KKP => ("069", "KKP", "KKPBTHBK", "Kiatnakin Phatra Bank", "ธนาคารเกียรตินาคินภัทร"),
+    ICBCT => ("070", "ICBCT", "ICBKTHBK", "Industrial and Commercial Bank of China (Thai)", "ธนาคารไอซีบีซี (ไทย)"),
+    TCD => ("071", "TCD", "THCETHB1", "Thai Credit Bank", "ธนาคารไทยเครดิต"),
+    LHB => ("073", "LHB", "LAHRTHB2", "Land and Houses Bank", "ธนาคารแลนด์ แอนด์ เฮ้าส์"),
+    ANZ => ("079", "ANZ", "ANZBTHBX", "ANZ Bank (Thai)", "ธนาคารเอเอ็นแซด (ไทย)"),
+    SMTB => ("080", "SMTB", "SMTBTHBK", "Sumitomo Mitsui Trust Bank (Thai)", "ธนาคารซูมิโตโม มิตซุย ทรัสต์ (ไทย)"),
+    SME => ("098", "SME", "SMEETHB1", "SME Development Bank", "ธนาคารพัฒนาวิสาหกิจขนาดกลางและขนาดย่อมแห่งประเทศไทย"),
+}
+
+impl BankCode {
+    // English name when known, otherwise the raw code
+    pub fn display_name(&self) -> &str {
+        self.name_en().unwrap_or_else(|| self.code())
+    }
+}
+
+impl From<String> for BankCode {
+    fn from(value: String) -> Self {
+        BankCode::from_code(&value)
+    }
+}
+
+impl From<BankCode> for String {
+    fn from(value: BankCode) -> Self {
+        value.code().to_string()
+    }
+}
+
+impl fmt::Display for BankCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+// Parse a 3 digit code or a short name, e.g. "004" or "kbank"
+impl FromStr for BankCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.len() == 3 && s.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(BankCode::from_code(s));
+        }
+        BankCode::ALL
+            .iter()
+            .find(|b| b.short_name().is_some_and(|n| n.eq_ignore_ascii_case(s)))
+            .cloned()
+            .ok_or_else(|| format!("unknown bank {}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bank_code() {
+        let bank = BankCode::from_code("004");
+        assert_eq!(bank, BankCode::KBANK);
+        assert_eq!(bank.display_name(), "Kasikornbank");
+        assert_eq!(bank.swift_code(), Some("KASITHBK"));
+        assert_eq!(BankCode::SCB.code(), "014");
+
+        let unknown = BankCode::from_code("999");
+        assert_eq!(unknown, BankCode::Unknown("999".to_string()));
+        assert_eq!(unknown.display_name(), "999");
+    }
+
+    #[test]
+    fn test_serde_bank_code() {
+        let bank: BankCode = serde_json::from_str(r#""002""#).unwrap();
+        assert_eq!(bank, BankCode::BBL);
+        assert_eq!(serde_json::to_string(&bank).unwrap(), r#""002""#);
+        let bank: BankCode = serde_json::from_str(r#""123""#).unwrap();
+        assert_eq!(serde_json::to_string(&bank).unwrap(), r#""123""#);
+    }
+
+    #[test]
+    fn test_parse_bank_code() {
+        assert_eq!("kbank".parse::<BankCode>(), Ok(BankCode::KBANK));
+        assert_eq!("014".parse::<BankCode>(), Ok(BankCode::SCB));
+        assert!("nobank".parse::<BankCode>().is_err());
+    }
+}
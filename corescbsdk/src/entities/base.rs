@@ -1,11 +1,14 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SCBAccessTokenRequest {
+// The secret is borrowed from the zeroised Credentials instead of being copied
+#[derive(Serialize, Deserialize)]
+pub struct SCBAccessTokenRequest<'a> {
     #[serde(rename = "applicationKey")]
     pub(crate) application_key: String,
     #[serde(rename = "applicationSecret")]
-    pub(crate) application_secret: String,
+    pub(crate) application_secret: &'a str,
     #[serde(rename = "authCode")]
     pub(crate) auth_code: Option<String>,
     #[serde(rename = "state")]
@@ -14,6 +17,18 @@ pub struct SCBAccessTokenRequest {
     pub(crate) code_challenge: Option<String>,
 }
 
+impl fmt::Debug for SCBAccessTokenRequest<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SCBAccessTokenRequest")
+            .field("application_key", &self.application_key)
+            .field("application_secret", &"***")
+            .field("auth_code", &self.auth_code)
+            .field("state", &self.state)
+            .field("code_challenge", &self.code_challenge)
+            .finish()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccessToken {
     #[serde(rename = "accessToken")]
@@ -32,6 +47,26 @@ pub struct AccessToken {
     pub(crate) refresh_expires_at: Option<i64>,
 }
 
+impl AccessToken {
+    pub fn access_token(&self) -> &str {
+        &self.access_token
+    }
+    pub fn expires_at(&self) -> i64 {
+        self.expires_at
+    }
+    pub fn is_expired(&self) -> bool {
+        chrono::Utc::now().timestamp() >= self.expires_at
+    }
+    // A refresh token is usable when it exists and has not passed refreshExpiresAt
+    pub fn can_refresh(&self) -> bool {
+        match (&self.refresh_token, self.refresh_expires_at) {
+            (Some(_), Some(expires_at)) => chrono::Utc::now().timestamp() < expires_at,
+            (Some(_), None) => true,
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SCBResponse<T> {
     #[serde(rename = "status")]
@@ -47,3 +82,23 @@ pub struct Status {
     #[serde(rename = "description")]
     pub(crate) description: String,
 }
+
+#[derive(Serialize, Deserialize)]
+pub struct SCBRefreshTokenRequest<'a> {
+    #[serde(rename = "applicationKey")]
+    pub(crate) application_key: String,
+    #[serde(rename = "applicationSecret")]
+    pub(crate) application_secret: &'a str,
+    #[serde(rename = "refreshToken")]
+    pub(crate) refresh_token: String,
+}
+
+impl fmt::Debug for SCBRefreshTokenRequest<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SCBRefreshTokenRequest")
+            .field("application_key", &self.application_key)
+            .field("application_secret", &"***")
+            .field("refresh_token", &"***")
+            .finish()
+    }
+}
@@ -1,9 +1,21 @@
-use serde::{Deserialize, Serialize};
-use validator::Validate;
+use std::fmt;
 
+use chrono::NaiveDate;
+use serde::{Deserialize, Deserializer, Serialize};
+use validator::{Validate, ValidationError};
+
+use crate::entities::bank_code::BankCode;
+use crate::entities::proxy::ProxyType;
 use crate::entities::validate::validate_data_type_date;
+use crate::errors::scb_error::SCBAPIError;
+
+// Thai QR Code Tag 30 (C Scan B)
+pub const EVENT_CODE_QR30_C_SCAN_B: &str = "00300100";
+// My Prompt QR (B Scan C)
+pub const EVENT_CODE_B_SCAN_C: &str = "00300104";
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_inquiry_references"))]
 pub struct BillPaymentInquiryRequest {
     // Event code of payment type
     // Possible value:
@@ -54,8 +66,177 @@ pub struct BillPaymentInquiryRequest {
     pub amount: Option<f64>,
 }
 
+// SCB requires the biller id and reference1 for C scan B, there is no biller wide inquiry
+fn validate_inquiry_references(request: &BillPaymentInquiryRequest) -> Result<(), ValidationError> {
+    if request.event_code == EVENT_CODE_QR30_C_SCAN_B
+        && (request.biller_id.as_deref().is_none_or(str::is_empty)
+            || request.reference1.as_deref().is_none_or(str::is_empty))
+    {
+        return Err(ValidationError::new("biller_id_and_reference1_required"));
+    }
+    Ok(())
+}
+
+impl BillPaymentInquiryRequest {
+    // Inquiry of the Thai QR tag 30 (C scan B) payments of a reference1 to a biller on one day
+    pub fn for_biller(transaction_date: &str, biller_id: &str, reference1: &str) -> Self {
+        BillPaymentInquiryRequest {
+            event_code: EVENT_CODE_QR30_C_SCAN_B.to_string(),
+            transaction_date: transaction_date.to_string(),
+            biller_id: Some(biller_id.to_string()),
+            reference1: Some(reference1.to_string()),
+            reference2: None,
+            partner_transaction_id: None,
+            amount: None,
+        }
+    }
+
+    // One C scan B inquiry per day, biller and reference1 from `from` to `to` inclusive,
+    // ordered by date, then biller
+    pub fn for_billers_between<S: AsRef<str>, R: AsRef<str>>(
+        biller_ids: &[S],
+        references1: &[R],
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Vec<Self> {
+        let mut requests = Vec::new();
+        for date in from.iter_days().take_while(|date| *date <= to) {
+            let transaction_date = date.format("%Y-%m-%d").to_string();
+            for biller_id in biller_ids {
+                for reference1 in references1 {
+                    requests.push(Self::for_biller(
+                        &transaction_date,
+                        biller_id.as_ref(),
+                        reference1.as_ref(),
+                    ));
+                }
+            }
+        }
+        requests
+    }
+
+    // Inquiry of a B scan C payment by the partner transaction id used to charge the customer
+    pub fn for_b_scan_c(transaction_date: &str, partner_transaction_id: &str) -> Self {
+        BillPaymentInquiryRequest {
+            event_code: EVENT_CODE_B_SCAN_C.to_string(),
+            transaction_date: transaction_date.to_string(),
+            biller_id: None,
+            reference1: None,
+            reference2: None,
+            partner_transaction_id: Some(partner_transaction_id.to_string()),
+            amount: None,
+        }
+    }
+}
+
+// Inquiry of one day that failed while pulling a range, other days are still returned
+#[derive(Debug)]
+pub struct BillPaymentInquiryError {
+    pub transaction_date: String,
+    pub biller_id: Option<String>,
+    pub reference1: Option<String>,
+    pub error: SCBAPIError,
+}
+
+impl fmt::Display for BillPaymentInquiryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "inquiry of {} for biller {} reference1 {} failed: {}",
+            self.transaction_date,
+            self.biller_id.as_deref().unwrap_or("-"),
+            self.reference1.as_deref().unwrap_or("-"),
+            self.error
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BillPaymentTransaction {}
+pub struct BillPaymentTransaction {
+    // Event code of payment type, see BillPaymentInquiryRequest
+    #[serde(rename = "eventCode")]
+    pub event_code: Option<String>,
+    // Type of transaction e.g. Domestic Transfers
+    #[serde(rename = "transactionType")]
+    pub transaction_type: Option<String>,
+    // Y if the transaction was reversed
+    #[serde(rename = "reverseFlag")]
+    pub reverse_flag: Option<String>,
+    // Biller ID or proxy of payee
+    #[serde(rename = "payeeProxyId")]
+    pub payee_proxy_id: Option<String>,
+    // Proxy type of payee e.g. BILLERID
+    #[serde(rename = "payeeProxyType")]
+    pub payee_proxy_type: Option<ProxyType>,
+    // Account number of payee
+    #[serde(rename = "payeeAccountNumber")]
+    pub payee_account_number: Option<String>,
+    // Name of payee
+    #[serde(rename = "payeeName")]
+    pub payee_name: Option<String>,
+    // Proxy of payer
+    #[serde(rename = "payerProxyId")]
+    pub payer_proxy_id: Option<String>,
+    // Proxy type of payer
+    #[serde(rename = "payerProxyType")]
+    pub payer_proxy_type: Option<ProxyType>,
+    // Account number of payer
+    #[serde(rename = "payerAccountNumber")]
+    pub payer_account_number: Option<String>,
+    // Name of payer
+    #[serde(rename = "payerName")]
+    pub payer_name: Option<String>,
+    // Sender Bank Code
+    #[serde(rename = "sendingBankCode")]
+    pub sending_bank_code: Option<BankCode>,
+    // Receiving Bank Code
+    #[serde(rename = "receivingBankCode")]
+    pub receiving_bank_code: Option<BankCode>,
+    // Transaction Amount
+    #[serde(rename = "amount", deserialize_with = "string_or_number")]
+    pub amount: String,
+    // Transaction ID from SCB
+    #[serde(rename = "transactionId")]
+    pub transaction_id: String,
+    // Transaction date and time, ISO 8601
+    #[serde(rename = "transactionDateandTime")]
+    pub transaction_date_and_time: String,
+    // Reference 1
+    #[serde(rename = "billPaymentRef1")]
+    pub bill_payment_ref1: Option<String>,
+    // Reference 2
+    #[serde(rename = "billPaymentRef2")]
+    pub bill_payment_ref2: Option<String>,
+    // Reference 3
+    #[serde(rename = "billPaymentRef3")]
+    pub bill_payment_ref3: Option<String>,
+    // Conditional
+    // B Scan C : Transaction ID from a partner
+    #[serde(rename = "partnerTransactionId")]
+    pub partner_transaction_id: Option<String>,
+    // Currency code as defined by ISO4217
+    // - 764 (Baht)
+    #[serde(rename = "currencyCode")]
+    pub currency_code: Option<String>,
+    // Channel used by the payer e.g. SCB EASY, other bank
+    #[serde(rename = "channelCode")]
+    pub channel_code: Option<String>,
+}
+
+// SCB returns amount either as "100.00" or 100.00 depending on the API version
+fn string_or_number<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(s) => Ok(s),
+        serde_json::Value::Number(n) => Ok(n.to_string()),
+        other => Err(serde::de::Error::custom(format!(
+            "invalid amount: {}",
+            other
+        ))),
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BillPaymentTransactionSlip {
@@ -64,10 +245,10 @@ pub struct BillPaymentTransactionSlip {
     pub trans_ref: String,
     // Sender Bank Code
     #[serde(rename = "sendingBank")]
-    pub sending_bank: String,
+    pub sending_bank: BankCode,
     // Receiving Bank Code
     #[serde(rename = "receivingBank")]
-    pub receiving_bank: String,
+    pub receiving_bank: BankCode,
     // Transaction Date
     #[serde(rename = "transDate")]
     pub trans_date: String,
@@ -137,9 +318,9 @@ pub struct Receiver {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Proxy {
-    // BILLERID
+    // e.g. BILLERID
     #[serde(rename = "type")]
-    pub type_field: String,
+    pub type_field: ProxyType,
     // BILLER ID
     #[serde(rename = "value")]
     pub value: String,
@@ -147,10 +328,76 @@ pub struct Proxy {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Account {
-    // BANKAC
+    // e.g. BANKAC
     #[serde(rename = "type")]
-    pub type_field: String,
+    pub type_field: ProxyType,
     // Account Number
     #[serde(rename = "value")]
     pub value: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_bill_payment_transaction() {
+        let txn: BillPaymentTransaction = serde_json::from_str(
+            r#"{
+                "eventCode": "00300100",
+                "payeeProxyId": "311040039475180",
+                "payeeProxyType": "BILLERID",
+                "amount": 100.5,
+                "transactionId": "50c1d9ca2ac04c33a3cde6b1d4e8dcbc",
+                "transactionDateandTime": "2019-08-22T14:34:57.000+07:00",
+                "billPaymentRef1": "REFERENCE1"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(txn.amount, "100.5");
+        assert_eq!(txn.bill_payment_ref1.as_deref(), Some("REFERENCE1"));
+    }
+
+    #[test]
+    fn test_for_billers_between() {
+        let from = NaiveDate::from_ymd_opt(2023, 1, 30).unwrap();
+        let to = NaiveDate::from_ymd_opt(2023, 2, 1).unwrap();
+        let requests =
+            BillPaymentInquiryRequest::for_billers_between(&["B1", "B2"], &["ORDER1"], from, to);
+        let keys: Vec<(&str, &str)> = requests
+            .iter()
+            .map(|r| (r.transaction_date.as_str(), r.biller_id.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("2023-01-30", "B1"),
+                ("2023-01-30", "B2"),
+                ("2023-01-31", "B1"),
+                ("2023-01-31", "B2"),
+                ("2023-02-01", "B1"),
+                ("2023-02-01", "B2"),
+            ]
+        );
+        assert!(requests
+            .iter()
+            .all(|r| r.reference1.as_deref() == Some("ORDER1") && r.validate().is_ok()));
+        assert!(
+            BillPaymentInquiryRequest::for_billers_between(&["B1"], &["ORDER1"], to, from)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_c_scan_b_inquiry_requires_reference1() {
+        let mut request = BillPaymentInquiryRequest::for_biller("2023-01-30", "B1", "ORDER1");
+        assert!(request.validate().is_ok());
+        request.reference1 = None;
+        assert!(request.validate().is_err());
+        assert!(
+            BillPaymentInquiryRequest::for_b_scan_c("2023-01-30", "PTX1")
+                .validate()
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomerProfileResponse {
+    // See CustomerProfile
+    #[serde(rename = "profile")]
+    pub profile: CustomerProfile,
+}
+
+// Profile of a customer who has granted consent to the partner.
+// Personal fields are masked in Debug output so the profile can be logged safely.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CustomerProfile {
+    // Unique id of the customer for this partner
+    #[serde(rename = "partnerId")]
+    pub partner_id: Option<String>,
+    // Thai national id, 13 digits
+    #[serde(rename = "citizenID")]
+    pub citizen_id: Option<String>,
+    // Passport number for foreign customers
+    #[serde(rename = "passportNumber")]
+    pub passport_number: Option<String>,
+    // Format: yyyy-MM-dd
+    #[serde(rename = "birthDate")]
+    pub birth_date: Option<String>,
+    // Title in Thai
+    #[serde(rename = "thaiTitle")]
+    pub thai_title: Option<String>,
+    #[serde(rename = "thaiFirstName")]
+    pub thai_first_name: Option<String>,
+    #[serde(rename = "thaiLastName")]
+    pub thai_last_name: Option<String>,
+    // Title in English
+    #[serde(rename = "engTitle")]
+    pub eng_title: Option<String>,
+    #[serde(rename = "engFirstName")]
+    pub eng_first_name: Option<String>,
+    #[serde(rename = "engLastName")]
+    pub eng_last_name: Option<String>,
+    // Gender code
+    // - M = Male
+    // - F = Female
+    #[serde(rename = "genderCode")]
+    pub gender_code: Option<String>,
+    // Mobile number registered with SCB
+    #[serde(rename = "mobile")]
+    pub mobile: Option<String>,
+    // Email registered with SCB
+    #[serde(rename = "email")]
+    pub email: Option<String>,
+    // See CustomerAddress
+    #[serde(rename = "address")]
+    pub address: Option<CustomerAddress>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CustomerAddress {
+    // House number, building, street
+    #[serde(rename = "address")]
+    pub address: Option<String>,
+    #[serde(rename = "subDistrict")]
+    pub sub_district: Option<String>,
+    #[serde(rename = "district")]
+    pub district: Option<String>,
+    #[serde(rename = "province")]
+    pub province: Option<String>,
+    #[serde(rename = "zipCode")]
+    pub zip_code: Option<String>,
+    // Country code e.g. TH
+    #[serde(rename = "country")]
+    pub country: Option<String>,
+}
+
+impl fmt::Debug for CustomerProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomerProfile")
+            .field("partner_id", &self.partner_id)
+            .field("citizen_id", &mask_tail(&self.citizen_id, 4))
+            .field("passport_number", &mask_tail(&self.passport_number, 3))
+            .field("birth_date", &mask_all(&self.birth_date))
+            .field("thai_title", &self.thai_title)
+            .field("thai_first_name", &mask_name(&self.thai_first_name))
+            .field("thai_last_name", &mask_name(&self.thai_last_name))
+            .field("eng_title", &self.eng_title)
+            .field("eng_first_name", &mask_name(&self.eng_first_name))
+            .field("eng_last_name", &mask_name(&self.eng_last_name))
+            .field("gender_code", &self.gender_code)
+            .field("mobile", &mask_tail(&self.mobile, 4))
+            .field("email", &mask_email(&self.email))
+            .field("address", &self.address)
+            .finish()
+    }
+}
+
+impl fmt::Debug for CustomerAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomerAddress")
+            .field("address", &mask_all(&self.address))
+            .field("sub_district", &self.sub_district)
+            .field("district", &self.district)
+            .field("province", &self.province)
+            .field("zip_code", &self.zip_code)
+            .field("country", &self.country)
+            .finish()
+    }
+}
+
+const MASK: char = '*';
+
+// Keep only the last `visible` characters, e.g. 1234567890123 -> *********0123
+fn mask_tail(value: &Option<String>, visible: usize) -> Option<String> {
+    value.as_ref().map(|v| {
+        let len = v.chars().count();
+        v.chars()
+            .enumerate()
+            .map(|(i, c)| if i + visible < len { MASK } else { c })
+            .collect()
+    })
+}
+
+// Keep only the first character, e.g. Somchai -> S******
+fn mask_name(value: &Option<String>) -> Option<String> {
+    value.as_ref().map(|v| {
+        v.chars()
+            .enumerate()
+            .map(|(i, c)| if i == 0 { c } else { MASK })
+            .collect()
+    })
+}
+
+// Keep the first character of the local part and the domain, e.g. somchai@scb.co.th -> s******@scb.co.th
+fn mask_email(value: &Option<String>) -> Option<String> {
+    value.as_ref().map(|v| match v.split_once('@') {
+        Some((local, domain)) => format!(
+            "{}@{}",
+            mask_name(&Some(local.to_string())).unwrap(),
+            domain
+        ),
+        None => mask_all(&Some(v.to_string())).unwrap(),
+    })
+}
+
+fn mask_all(value: &Option<String>) -> Option<String> {
+    value.as_ref().map(|v| v.chars().map(|_| MASK).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_masks_pii() {
+        let profile: CustomerProfile = serde_json::from_str(
+            r#"{
+                "partnerId": "P001",
+                "citizenID": "1234567890123",
+                "thaiFirstName": "สมชาย",
+                "engFirstName": "Somchai",
+                "mobile": "0812345678",
+                "email": "somchai@example.com",
+                "address": {"address": "1 Ratchadaphisek Rd", "province": "Bangkok"}
+            }"#,
+        )
+        .unwrap();
+        let debug = format!("{:?}", profile);
+        assert!(debug.contains("*********0123"));
+        assert!(debug.contains("S******"));
+        assert!(debug.contains("s******@example.com"));
+        assert!(debug.contains("******5678"));
+        assert!(debug.contains("Bangkok"));
+        assert!(!debug.contains("1234567890123"));
+        assert!(!debug.contains("Somchai"));
+        assert!(!debug.contains("สมชาย"));
+        assert!(!debug.contains("Ratchadaphisek"));
+    }
+}
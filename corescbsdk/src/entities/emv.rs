@@ -0,0 +1,145 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+use serde::{Deserialize, Serialize};
+
+// A data object of an EMVCo / Thai QR payload: 2 digit tag, 2 digit length, value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmvTlv {
+    pub tag: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmvError {
+    // Payload ends in the middle of a tag, length or value
+    Truncated { position: usize },
+    // Length field is not 2 digits
+    InvalidLength { position: usize },
+    // CRC tag is not present as the last data object
+    MissingCrc,
+    // CRC of payload does not match the CRC data object
+    CrcMismatch { expected: String, actual: String },
+}
+
+impl fmt::Display for EmvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EmvError::Truncated { position } => write!(f, "payload truncated at {}", position),
+            EmvError::InvalidLength { position } => {
+                write!(f, "invalid length field at {}", position)
+            }
+            EmvError::MissingCrc => write!(f, "CRC data object is missing"),
+            EmvError::CrcMismatch { expected, actual } => {
+                write!(f, "CRC mismatch, expected {} but was {}", expected, actual)
+            }
+        }
+    }
+}
+
+// Parse a flat list of TLV data objects. Nested templates can be parsed again from their value.
+pub fn parse_tlv(payload: &str) -> Result<Vec<EmvTlv>, EmvError> {
+    let chars: Vec<char> = payload.chars().collect();
+    let mut objects = Vec::new();
+    let mut position = 0;
+
+    while position < chars.len() {
+        if position + 4 > chars.len() {
+            return Err(EmvError::Truncated { position });
+        }
+        let tag: String = chars[position..position + 2].iter().collect();
+        let length: String = chars[position + 2..position + 4].iter().collect();
+        let length: usize = length
+            .parse()
+            .map_err(|_| EmvError::InvalidLength { position })?;
+        let start = position + 4;
+        if start + length > chars.len() {
+            return Err(EmvError::Truncated { position });
+        }
+        objects.push(EmvTlv {
+            tag,
+            value: chars[start..start + length].iter().collect(),
+        });
+        position = start + length;
+    }
+    Ok(objects)
+}
+
+pub fn find_tag<'a>(objects: &'a [EmvTlv], tag: &str) -> Option<&'a str> {
+    objects
+        .iter()
+        .find(|o| o.tag == tag)
+        .map(|o| o.value.as_str())
+}
+
+// CRC-16/CCITT-FALSE (poly 0x1021, init 0xFFFF) used by EMVCo and Thai QR payloads
+pub fn crc16_ccitt(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            if crc & 0x8000 != 0 {
+                crc = (crc << 1) ^ 0x1021;
+            } else {
+                crc <<= 1;
+            }
+        }
+    }
+    crc
+}
+
+// Verify the CRC data object `crc_tag` which must be the last object of the payload.
+// The CRC covers the payload up to and including the tag and length of the CRC object.
+pub fn verify_crc(payload: &str, crc_tag: &str) -> Result<(), EmvError> {
+    let objects = parse_tlv(payload)?;
+    let crc = match objects.last() {
+        Some(last) if last.tag == crc_tag && last.value.len() == 4 => last.value.to_uppercase(),
+        _ => return Err(EmvError::MissingCrc),
+    };
+    let covered = &payload[..payload.len() - 4];
+    let actual = format!("{:04X}", crc16_ccitt(covered.as_bytes()));
+    if actual != crc {
+        return Err(EmvError::CrcMismatch {
+            expected: crc,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc16_ccitt() {
+        assert_eq!(crc16_ccitt(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn test_parse_tlv() {
+        let objects = parse_tlv("000201010212").unwrap();
+        assert_eq!(objects.len(), 2);
+        assert_eq!(find_tag(&objects, "01"), Some("12"));
+        assert_eq!(
+            parse_tlv("00020101"),
+            Err(EmvError::Truncated { position: 6 })
+        );
+        assert_eq!(
+            parse_tlv("00AB01"),
+            Err(EmvError::InvalidLength { position: 0 })
+        );
+    }
+
+    #[test]
+    fn test_verify_crc() {
+        let body = "0002015802TH6304";
+        let payload = format!("{}{:04X}", body, crc16_ccitt(body.as_bytes()));
+        assert!(verify_crc(&payload, "63").is_ok());
+        assert_eq!(verify_crc("0002015802TH", "63"), Err(EmvError::MissingCrc));
+        assert!(matches!(
+            verify_crc("0002015802TH63040000", "63"),
+            Err(EmvError::CrcMismatch { .. })
+        ));
+    }
+}
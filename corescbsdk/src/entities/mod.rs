@@ -1,5 +1,17 @@
-pub(crate) mod base;
+pub mod bank_code;
+pub mod base;
+pub mod proxy;
 pub mod qr_code;
 
+pub mod oauth;
+
+pub mod b_scan_c;
 pub mod bill_pay;
+pub mod customer;
+pub mod emv;
+pub mod payment_session;
+pub mod reconciliation;
+pub mod reference;
+pub mod slip_policy;
+pub mod slip_qr;
 mod validate;
@@ -0,0 +1,117 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::errors::scb_error::SCBAPIError;
+
+pub const CODE_CHALLENGE_METHOD_S256: &str = "S256";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PkceChallenge {
+    // High-entropy secret kept by the partner until the auth code is exchanged.
+    // Length: 43 - 128 characters (RFC 7636)
+    pub code_verifier: String,
+    // BASE64URL(SHA256(code_verifier)) sent with the authorize request
+    pub code_challenge: String,
+    // Always "S256"
+    pub code_challenge_method: String,
+}
+
+impl PkceChallenge {
+    pub fn new() -> Self {
+        // Two random UUIDs in simple form give a 64 character verifier of unreserved characters
+        let verifier = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        Self::from_verifier(&verifier)
+    }
+
+    pub fn from_verifier(code_verifier: &str) -> Self {
+        let digest = Sha256::digest(code_verifier.as_bytes());
+        PkceChallenge {
+            code_verifier: code_verifier.to_string(),
+            code_challenge: URL_SAFE_NO_PAD.encode(digest),
+            code_challenge_method: CODE_CHALLENGE_METHOD_S256.to_string(),
+        }
+    }
+}
+
+impl Default for PkceChallenge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Everything the partner has to keep between redirecting the customer to SCB
+// and receiving the auth code back on the redirect URL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorizationSession {
+    // Opaque value echoed back by SCB on the redirect URL
+    pub state: String,
+    // PKCE verifier and challenge for this session
+    pub pkce: PkceChallenge,
+    // URL the customer has to open to give consent (callbackUrl from SCB)
+    pub authorize_url: String,
+}
+
+impl AuthorizationSession {
+    pub fn verify_state(&self, state: &str) -> Result<(), SCBAPIError> {
+        if self.state.len() != state.len()
+            || self
+                .state
+                .bytes()
+                .zip(state.bytes())
+                .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+                != 0
+        {
+            return Err(SCBAPIError::AuthorizationError(
+                "state returned by SCB does not match the authorization session".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuthorizeResponse {
+    // URL to open SCB EASY app / web for customer consent
+    #[serde(rename = "callbackUrl")]
+    pub callback_url: String,
+}
+
+pub(crate) fn generate_state() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pkce_challenge_rfc7636() {
+        let pkce = PkceChallenge::from_verifier("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk");
+        assert_eq!(
+            pkce.code_challenge,
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+        assert_eq!(pkce.code_challenge_method, "S256");
+    }
+
+    #[test]
+    fn test_pkce_verifier_length() {
+        let pkce = PkceChallenge::new();
+        assert!(pkce.code_verifier.len() >= 43 && pkce.code_verifier.len() <= 128);
+    }
+
+    #[test]
+    fn test_verify_state() {
+        let session = AuthorizationSession {
+            state: "abc123".to_string(),
+            pkce: PkceChallenge::new(),
+            authorize_url: "https://example.com".to_string(),
+        };
+        assert!(session.verify_state("abc123").is_ok());
+        assert!(session.verify_state("abc124").is_err());
+        assert!(session.verify_state("abc").is_err());
+    }
+}
@@ -0,0 +1,232 @@
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+
+use crate::entities::bill_pay::{BillPaymentInquiryRequest, BillPaymentTransaction};
+use crate::entities::slip_policy::BANGKOK_OFFSET_SECONDS;
+use crate::entities::validate::parse_satang;
+use crate::errors::scb_error::SCBAPIError;
+
+// Delay before the first inquiry, doubled after every unpaid inquiry
+const DEFAULT_INITIAL_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_MAX_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub enum PaymentSessionState {
+    // QR created, not polled yet
+    Created,
+    // Waiting for the customer to pay
+    Pending,
+    Paid(Box<BillPaymentTransaction>),
+    // Deadline passed without payment
+    Expired,
+}
+
+#[derive(Debug)]
+pub enum PaymentOutcome {
+    Paid(Box<BillPaymentTransaction>),
+    Expired,
+    Error(SCBAPIError),
+}
+
+// Exponential backoff between inquiries while waiting for a payment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollBackoff {
+    pub initial: Duration,
+    pub max: Duration,
+}
+
+impl Default for PollBackoff {
+    fn default() -> Self {
+        PollBackoff {
+            initial: DEFAULT_INITIAL_POLL_INTERVAL,
+            max: DEFAULT_MAX_POLL_INTERVAL,
+        }
+    }
+}
+
+impl PollBackoff {
+    // Delay before the inquiry number `attempt` (starting at 0)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt);
+        self.initial.saturating_mul(factor).min(self.max)
+    }
+}
+
+// Payment expected for a C scan B QR, tracked from creation until paid or expired
+#[derive(Debug, Clone)]
+pub struct PaymentSession {
+    // Biller ID the QR was created for
+    pub biller_id: String,
+    pub reference1: String,
+    pub reference2: Option<String>,
+    // Expected amount e.g. 100.00
+    pub amount: String,
+    pub created_at: DateTime<Utc>,
+    pub deadline: DateTime<Utc>,
+    pub backoff: PollBackoff,
+    state: PaymentSessionState,
+}
+
+impl PaymentSession {
+    pub fn new(
+        biller_id: &str,
+        reference1: &str,
+        reference2: Option<&str>,
+        amount: &str,
+        deadline: DateTime<Utc>,
+    ) -> Self {
+        PaymentSession {
+            biller_id: biller_id.to_string(),
+            reference1: reference1.to_string(),
+            reference2: reference2.map(|s| s.to_string()),
+            amount: amount.to_string(),
+            created_at: Utc::now(),
+            deadline,
+            backoff: PollBackoff::default(),
+            state: PaymentSessionState::Created,
+        }
+    }
+
+    pub fn state(&self) -> &PaymentSessionState {
+        &self.state
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.state,
+            PaymentSessionState::Paid(_) | PaymentSessionState::Expired
+        )
+    }
+
+    // Created -> Pending, a finished session is left as is
+    pub fn mark_pending(&mut self) {
+        if matches!(self.state, PaymentSessionState::Created) {
+            self.state = PaymentSessionState::Pending;
+        }
+    }
+
+    pub fn mark_paid(&mut self, transaction: BillPaymentTransaction) {
+        if !self.is_finished() {
+            self.state = PaymentSessionState::Paid(Box::new(transaction));
+        }
+    }
+
+    // Expire the session if the deadline has passed at `now`, returns true when expired
+    pub fn expire_if_due(&mut self, now: DateTime<Utc>) -> bool {
+        if !self.is_finished() && now >= self.deadline {
+            self.state = PaymentSessionState::Expired;
+        }
+        matches!(self.state, PaymentSessionState::Expired)
+    }
+
+    // Same references and amount, and not reversed
+    pub fn matches(&self, transaction: &BillPaymentTransaction) -> bool {
+        transaction.reverse_flag.as_deref() != Some("Y")
+            && transaction.bill_payment_ref1.as_deref() == Some(self.reference1.as_str())
+            && self
+                .reference2
+                .as_ref()
+                .is_none_or(|ref2| transaction.bill_payment_ref2.as_deref() == Some(ref2))
+            && self.matches_amount(&transaction.amount)
+    }
+
+    // Same amount in satang, e.g. 100 and 100.00
+    pub fn matches_amount(&self, amount: &str) -> bool {
+        parse_satang(amount).is_some() && parse_satang(amount) == parse_satang(&self.amount)
+    }
+
+    // Inquiries covering the session, one per Thailand calendar day from creation until `now`
+    pub fn inquiry_requests(&self, now: DateTime<Utc>) -> Vec<BillPaymentInquiryRequest> {
+        let first = bangkok_date(self.created_at);
+        let last = bangkok_date(now.min(self.deadline)).max(first);
+        first
+            .iter_days()
+            .take_while(|date| *date <= last)
+            .map(|date| {
+                let mut request = BillPaymentInquiryRequest::for_biller(
+                    &date.format("%Y-%m-%d").to_string(),
+                    &self.biller_id,
+                    &self.reference1,
+                );
+                request.reference2 = self.reference2.clone();
+                request
+            })
+            .collect()
+    }
+}
+
+fn bangkok_date(time: DateTime<Utc>) -> NaiveDate {
+    let offset = FixedOffset::east_opt(BANGKOK_OFFSET_SECONDS).unwrap();
+    time.with_timezone(&offset).date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn transaction(ref1: &str, amount: &str, reverse_flag: Option<&str>) -> BillPaymentTransaction {
+        serde_json::from_value(serde_json::json!({
+            "transactionId": "T1",
+            "transactionDateandTime": "2023-01-01T12:00:00+07:00",
+            "amount": amount,
+            "billPaymentRef1": ref1,
+            "reverseFlag": reverse_flag
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_session_states() {
+        let deadline = Utc.with_ymd_and_hms(2023, 1, 1, 5, 0, 0).unwrap();
+        let mut session = PaymentSession::new("311040039475180", "ORDER1", None, "100", deadline);
+        assert!(matches!(session.state(), PaymentSessionState::Created));
+        session.mark_pending();
+        assert!(matches!(session.state(), PaymentSessionState::Pending));
+        assert!(!session.expire_if_due(deadline - chrono::Duration::seconds(1)));
+        assert!(session.expire_if_due(deadline));
+        // A finished session does not change any more
+        session.mark_paid(transaction("ORDER1", "100", None));
+        assert!(matches!(session.state(), PaymentSessionState::Expired));
+    }
+
+    #[test]
+    fn test_session_matches() {
+        let session = PaymentSession::new("311040039475180", "ORDER1", None, "100.00", Utc::now());
+        assert!(session.matches(&transaction("ORDER1", "100", None)));
+        assert!(!session.matches(&transaction("ORDER1", "99", None)));
+        assert!(!session.matches(&transaction("ORDER2", "100", None)));
+        assert!(!session.matches(&transaction("ORDER1", "100", Some("Y"))));
+    }
+
+    #[test]
+    fn test_inquiry_requests_cross_midnight() {
+        let mut session = PaymentSession::new(
+            "311040039475180",
+            "ORDER1",
+            Some("CUST1"),
+            "100",
+            Utc.with_ymd_and_hms(2023, 1, 1, 18, 0, 0).unwrap(),
+        );
+        // 23:50 and 00:10 Thailand time
+        session.created_at = Utc.with_ymd_and_hms(2023, 1, 1, 16, 50, 0).unwrap();
+        let now = Utc.with_ymd_and_hms(2023, 1, 1, 17, 10, 0).unwrap();
+        let dates: Vec<String> = session
+            .inquiry_requests(now)
+            .into_iter()
+            .map(|r| r.transaction_date)
+            .collect();
+        assert_eq!(dates, vec!["2023-01-01", "2023-01-02"]);
+    }
+
+    #[test]
+    fn test_poll_backoff() {
+        let backoff = PollBackoff::default();
+        assert_eq!(backoff.delay(0), Duration::from_secs(2));
+        assert_eq!(backoff.delay(2), Duration::from_secs(8));
+        assert_eq!(backoff.delay(10), Duration::from_secs(30));
+        assert_eq!(backoff.delay(64), Duration::from_secs(30));
+    }
+}
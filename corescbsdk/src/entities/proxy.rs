@@ -0,0 +1,217 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+use serde::{Deserialize, Serialize};
+
+// Kind of PromptPay proxy or account, used for ppType, proxy.type and account.type
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum ProxyType {
+    // BILLERID, 15 digits
+    BillerId,
+    // MSISDN, mobile number in 0066XXXXXXXXX format
+    MobileNumber,
+    // NATID, Thai national id, 13 digits with check digit
+    NationalId,
+    // TAXID, 13 digits with check digit
+    TaxId,
+    // EWALLETID, 15 digits
+    EWalletId,
+    // BANKAC, bank account number
+    BankAccount,
+    // Any type not known by this SDK, kept as sent by SCB
+    Unknown(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProxyError {
+    // Value has the wrong length or characters for the proxy type
+    InvalidFormat {
+        proxy_type: ProxyType,
+        value: String,
+    },
+    // National id or tax id check digit does not match
+    InvalidChecksum {
+        proxy_type: ProxyType,
+        value: String,
+    },
+}
+
+impl fmt::Display for ProxyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ProxyError::InvalidFormat { proxy_type, value } => {
+                write!(f, "invalid {} value {}", proxy_type, value)
+            }
+            ProxyError::InvalidChecksum { proxy_type, value } => {
+                write!(f, "invalid check digit of {} value {}", proxy_type, value)
+            }
+        }
+    }
+}
+
+impl ProxyType {
+    // Validate a proxy value and convert it to the format expected by SCB,
+    // e.g. mobile number 081-234-5678 -> 0066812345678
+    pub fn normalize(&self, value: &str) -> Result<String, ProxyError> {
+        let digits: String = value
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .collect();
+        let invalid_format = || ProxyError::InvalidFormat {
+            proxy_type: self.clone(),
+            value: value.to_string(),
+        };
+        if let ProxyType::Unknown(_) = self {
+            return Ok(digits);
+        }
+        let digits = digits.strip_prefix('+').unwrap_or(&digits).to_string();
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid_format());
+        }
+
+        match self {
+            ProxyType::MobileNumber => {
+                let national = if let Some(n) = digits.strip_prefix("0066") {
+                    n
+                } else if let Some(n) = digits.strip_prefix("66") {
+                    n
+                } else if let Some(n) = digits.strip_prefix('0') {
+                    n
+                } else {
+                    return Err(invalid_format());
+                };
+                if national.len() != 9 || national.starts_with('0') {
+                    return Err(invalid_format());
+                }
+                Ok(format!("0066{}", national))
+            }
+            ProxyType::NationalId | ProxyType::TaxId => {
+                if digits.len() != 13 {
+                    return Err(invalid_format());
+                }
+                if !valid_thai_id_checksum(&digits) {
+                    return Err(ProxyError::InvalidChecksum {
+                        proxy_type: self.clone(),
+                        value: value.to_string(),
+                    });
+                }
+                Ok(digits)
+            }
+            ProxyType::BillerId | ProxyType::EWalletId => {
+                if digits.len() != 15 {
+                    return Err(invalid_format());
+                }
+                Ok(digits)
+            }
+            ProxyType::BankAccount => {
+                if !(10..=15).contains(&digits.len()) {
+                    return Err(invalid_format());
+                }
+                Ok(digits)
+            }
+            ProxyType::Unknown(_) => Ok(digits),
+        }
+    }
+}
+
+// Check digit of Thai national id and tax id:
+// (11 - sum(digit[i] * (13 - i)) mod 11) mod 10 for the first 12 digits
+fn valid_thai_id_checksum(digits: &str) -> bool {
+    let digits: Vec<u32> = digits.chars().filter_map(|c| c.to_digit(10)).collect();
+    if digits.len() != 13 {
+        return false;
+    }
+    let sum: u32 = digits[..12]
+        .iter()
+        .enumerate()
+        .map(|(i, d)| d * (13 - i as u32))
+        .sum();
+    (11 - sum % 11) % 10 == digits[12]
+}
+
+impl From<String> for ProxyType {
+    fn from(value: String) -> Self {
+        match value.trim().to_uppercase().as_str() {
+            "BILLERID" => ProxyType::BillerId,
+            "MSISDN" | "MOBILE" => ProxyType::MobileNumber,
+            "NATID" => ProxyType::NationalId,
+            "TAXID" => ProxyType::TaxId,
+            "EWALLETID" => ProxyType::EWalletId,
+            "BANKAC" | "ACCOUNT" => ProxyType::BankAccount,
+            _ => ProxyType::Unknown(value),
+        }
+    }
+}
+
+impl From<ProxyType> for String {
+    fn from(value: ProxyType) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for ProxyType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ProxyType::BillerId => write!(f, "BILLERID"),
+            ProxyType::MobileNumber => write!(f, "MSISDN"),
+            ProxyType::NationalId => write!(f, "NATID"),
+            ProxyType::TaxId => write!(f, "TAXID"),
+            ProxyType::EWalletId => write!(f, "EWALLETID"),
+            ProxyType::BankAccount => write!(f, "BANKAC"),
+            ProxyType::Unknown(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_mobile_number() {
+        let msisdn = ProxyType::MobileNumber;
+        assert_eq!(msisdn.normalize("0812345678").unwrap(), "0066812345678");
+        assert_eq!(msisdn.normalize("081-234-5678").unwrap(), "0066812345678");
+        assert_eq!(msisdn.normalize("+66812345678").unwrap(), "0066812345678");
+        assert_eq!(msisdn.normalize("0066812345678").unwrap(), "0066812345678");
+        assert!(msisdn.normalize("081234567").is_err());
+        assert!(msisdn.normalize("812345678").is_err());
+    }
+
+    #[test]
+    fn test_normalize_national_id() {
+        let natid = ProxyType::NationalId;
+        assert_eq!(
+            natid.normalize("1-1017-00230-70-8").unwrap(),
+            "1101700230708"
+        );
+        assert!(matches!(
+            natid.normalize("1101700230709"),
+            Err(ProxyError::InvalidChecksum { .. })
+        ));
+        assert!(matches!(
+            natid.normalize("110170023070"),
+            Err(ProxyError::InvalidFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn test_normalize_biller_id() {
+        assert!(ProxyType::BillerId.normalize("311040039475180").is_ok());
+        assert!(ProxyType::BillerId.normalize("31104003947518").is_err());
+        assert!(ProxyType::BankAccount.normalize("123-4-56789-0").is_ok());
+    }
+
+    #[test]
+    fn test_serde_proxy_type() {
+        let proxy_type: ProxyType = serde_json::from_str(r#""BANKAC""#).unwrap();
+        assert_eq!(proxy_type, ProxyType::BankAccount);
+        let proxy_type: ProxyType = serde_json::from_str(r#""""#).unwrap();
+        assert_eq!(proxy_type, ProxyType::Unknown("".to_string()));
+        assert_eq!(
+            serde_json::to_string(&ProxyType::MobileNumber).unwrap(),
+            r#""MSISDN""#
+        );
+    }
+}
@@ -4,6 +4,7 @@ use std::fmt::Formatter;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
+use crate::entities::proxy::ProxyType;
 use crate::entities::validate::{validate_amount, validate_data_type_az09};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
@@ -40,7 +41,7 @@ pub struct QRCodeRequest {
     // PromptPay Type for QR 30
     // Value: BILLERID
     #[serde(rename = "ppType", skip_serializing_if = "Option::is_none")]
-    pp_type: Option<String>,
+    pp_type: Option<ProxyType>,
     // Biller ID
     //
     // Note: Partners can get on merchant profile of their application.
@@ -91,6 +92,30 @@ impl fmt::Display for QRCodeType {
     }
 }
 
+impl QRCodeRequest {
+    pub fn qr_type(&self) -> &str {
+        &self.qr_type
+    }
+    pub fn amount(&self) -> &str {
+        &self.amount
+    }
+    pub fn pp_id(&self) -> Option<&str> {
+        self.pp_id.as_deref()
+    }
+    pub fn invoice(&self) -> Option<&str> {
+        self.invoice.as_deref()
+    }
+    pub fn ref1(&self) -> Option<&str> {
+        self.ref1.as_deref()
+    }
+    pub fn ref2(&self) -> Option<&str> {
+        self.ref2.as_deref()
+    }
+    pub fn ref3(&self) -> Option<&str> {
+        self.ref3.as_deref()
+    }
+}
+
 pub struct QRCodeRequestBuilder {
     qrcode_request: QRCodeRequest,
 }
@@ -128,12 +153,12 @@ impl QRCodeRequestBuilder {
     }
     pub fn for_qr_tag30(
         &mut self,
-        pp_type: &String,
+        pp_type: &ProxyType,
         pp_id: &String,
         ref1: &String,
         ref3: &String,
     ) -> &mut Self {
-        self.qrcode_request.pp_type = Some(pp_type.to_string());
+        self.qrcode_request.pp_type = Some(pp_type.clone());
         self.qrcode_request.pp_id = Some(pp_id.to_string());
         self.qrcode_request.ref1 = Some(ref1.to_string());
         self.qrcode_request.ref3 = Some(ref3.to_string());
@@ -157,7 +182,11 @@ impl QRCodeRequestBuilder {
     }
 
     pub fn build(&self) -> Result<QRCodeRequest, &'static str> {
-        Ok(self.qrcode_request.clone())
+        let mut qrcode_request = self.qrcode_request.clone();
+        if let (Some(pp_type), Some(pp_id)) = (&qrcode_request.pp_type, &qrcode_request.pp_id) {
+            qrcode_request.pp_id = Some(pp_type.normalize(pp_id).map_err(|_| "invalid ppId")?);
+        }
+        Ok(qrcode_request)
     }
 }
 
@@ -253,3 +282,172 @@ pub struct Channel {
     #[serde(rename = "channelCode")]
     pub channel_code: Option<String>,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum QRCreditCardStatus {
+    // QR created, customer has not paid yet
+    Pending,
+    // Customer paid with credit card
+    Paid,
+    // QR cancelled by partner
+    Cancelled,
+    // QR passed csExtExpiryTime without payment
+    Expired,
+    // Payment rejected by card issuer
+    Failed,
+    // Any status not known by this SDK
+    Unknown(String),
+}
+
+impl From<String> for QRCreditCardStatus {
+    fn from(value: String) -> Self {
+        match value.to_uppercase().as_str() {
+            "PENDING" | "UNPAID" | "CREATED" => QRCreditCardStatus::Pending,
+            "PAID" | "SUCCESS" | "APPROVED" => QRCreditCardStatus::Paid,
+            "CANCELLED" | "CANCELED" | "VOIDED" => QRCreditCardStatus::Cancelled,
+            "EXPIRED" => QRCreditCardStatus::Expired,
+            "FAILED" | "DECLINED" | "REJECTED" => QRCreditCardStatus::Failed,
+            _ => QRCreditCardStatus::Unknown(value),
+        }
+    }
+}
+
+impl From<QRCreditCardStatus> for String {
+    fn from(value: QRCreditCardStatus) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for QRCreditCardStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            QRCreditCardStatus::Pending => write!(f, "PENDING"),
+            QRCreditCardStatus::Paid => write!(f, "PAID"),
+            QRCreditCardStatus::Cancelled => write!(f, "CANCELLED"),
+            QRCreditCardStatus::Expired => write!(f, "EXPIRED"),
+            QRCreditCardStatus::Failed => write!(f, "FAILED"),
+            QRCreditCardStatus::Unknown(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QRCreditCardTransaction {
+    // Unique id of QR code returned by qr_code_create (qrCodeId)
+    #[serde(rename = "qrId")]
+    pub qr_code_id: String,
+    // Status of the QR CS transaction
+    #[serde(rename = "txnStatus")]
+    pub status: QRCreditCardStatus,
+    // Status "000" is Success
+    #[serde(rename = "responseCode")]
+    pub response_code: Option<String>,
+    // Invoice or bill number for the transaction
+    #[serde(rename = "invoice")]
+    pub invoice: Option<String>,
+    // Merchant ID set by the company
+    #[serde(rename = "merchantId")]
+    pub merchant_id: Option<String>,
+    // Terminal unique ID
+    #[serde(rename = "terminalId")]
+    pub terminal_id: Option<String>,
+    // Amount paid for the transaction
+    #[serde(rename = "amount")]
+    pub amount: Option<String>,
+    // Currency code as defined by ISO4217
+    // - 764 (Baht)
+    #[serde(rename = "currencyCode")]
+    pub currency_code: Option<String>,
+    // Conditional
+    // Paid : Transaction number from card acquirer
+    #[serde(rename = "txnNumber")]
+    pub txn_number: Option<String>,
+    // Conditional
+    // Paid : Approval code from card issuer
+    #[serde(rename = "approvalCode")]
+    pub approval_code: Option<String>,
+    // Conditional
+    // Paid : Card scheme e.g. VISA, MASTERCARD
+    #[serde(rename = "cardType")]
+    pub card_type: Option<String>,
+    // Conditional
+    // Paid : Masked card number e.g. 411111XXXXXX1111
+    #[serde(rename = "maskedCardNo")]
+    pub masked_card_number: Option<String>,
+    // Conditional
+    // Paid : Transaction date and time, ISO 8601
+    #[serde(rename = "txnDateTime")]
+    pub txn_date_time: Option<String>,
+}
+
+impl QRCreditCardTransaction {
+    pub fn is_paid(&self) -> bool {
+        self.status == QRCreditCardStatus::Paid
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QRCodeCancelResponse {
+    // Unique id of the cancelled QR code
+    #[serde(rename = "qrId")]
+    pub qr_code_id: Option<String>,
+    // Status "000" is Success
+    #[serde(rename = "responseCode")]
+    pub response_code: Option<String>,
+    // Status of the QR CS transaction after cancellation
+    #[serde(rename = "txnStatus")]
+    pub status: Option<QRCreditCardStatus>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qr_credit_card_status() {
+        let txn: QRCreditCardTransaction = serde_json::from_str(
+            r#"{"qrId": "QR123", "txnStatus": "PAID", "responseCode": "000", "amount": "100.00"}"#,
+        )
+        .unwrap();
+        assert!(txn.is_paid());
+
+        let status: QRCreditCardStatus = serde_json::from_str(r#""REFUNDED""#).unwrap();
+        assert_eq!(status, QRCreditCardStatus::Unknown("REFUNDED".to_string()));
+        assert_eq!(serde_json::to_string(&status).unwrap(), r#""REFUNDED""#);
+        assert_eq!(
+            serde_json::to_string(&QRCreditCardStatus::Cancelled).unwrap(),
+            r#""CANCELLED""#
+        );
+    }
+
+    #[test]
+    fn test_build_normalizes_pp_id() {
+        let request = QRCodeRequestBuilder::new(&QRCodeType::PP, &"100.00".to_string())
+            .for_qr_tag30(
+                &ProxyType::BillerId,
+                &"3110-4003-9475-180".to_string(),
+                &"REFERENCE1".to_string(),
+                &"SCB1".to_string(),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(request.pp_id.as_deref(), Some("311040039475180"));
+        assert_eq!(
+            serde_json::to_value(&request).unwrap()["ppType"],
+            "BILLERID"
+        );
+
+        assert!(
+            QRCodeRequestBuilder::new(&QRCodeType::PP, &"100.00".to_string())
+                .for_qr_tag30(
+                    &ProxyType::BillerId,
+                    &"1234".to_string(),
+                    &"REFERENCE1".to_string(),
+                    &"SCB1".to_string(),
+                )
+                .build()
+                .is_err()
+        );
+    }
+}
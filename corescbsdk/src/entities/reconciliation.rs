@@ -0,0 +1,235 @@
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::entities::bill_pay::BillPaymentTransaction;
+use crate::entities::validate::parse_satang;
+
+// Payment the partner expects to receive, e.g. one per order created during the day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedPayment {
+    // Reference 1 of the QR
+    pub reference1: String,
+    // Reference 2 of the QR, when set only transactions with the same ref2 match
+    pub reference2: Option<String>,
+    // Order total e.g. 100.00
+    pub amount: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciledPayment {
+    pub expected: ExpectedPayment,
+    pub transaction: BillPaymentTransaction,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    // First transaction date included, yyyy-MM-dd
+    pub from: Option<String>,
+    // Last transaction date included, yyyy-MM-dd
+    pub to: Option<String>,
+    // Paid with the expected amount
+    pub matched: Vec<ReconciledPayment>,
+    // No transaction with the references of the expected payment
+    pub missing: Vec<ExpectedPayment>,
+    // Transaction which does not belong to any expected payment, including
+    // a second payment of the same references
+    pub unexpected: Vec<BillPaymentTransaction>,
+    // Paid with an amount different from the expected amount
+    pub amount_mismatch: Vec<ReconciledPayment>,
+}
+
+// One line per expected payment or transaction in the CSV export
+#[derive(Debug, Serialize)]
+struct ReconciliationRow<'a> {
+    status: &'static str,
+    reference1: Option<&'a str>,
+    reference2: Option<&'a str>,
+    expected_amount: Option<&'a str>,
+    paid_amount: Option<&'a str>,
+    transaction_id: Option<&'a str>,
+    transaction_date_time: Option<&'a str>,
+}
+
+impl ReconciliationReport {
+    pub fn is_balanced(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty() && self.amount_mismatch.is_empty()
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn write_csv<W: io::Write>(&self, writer: W) -> Result<(), csv::Error> {
+        let mut writer = csv::Writer::from_writer(writer);
+        for (status, payments) in [
+            ("matched", &self.matched),
+            ("amount_mismatch", &self.amount_mismatch),
+        ] {
+            for p in payments {
+                writer.serialize(ReconciliationRow {
+                    status,
+                    reference1: Some(&p.expected.reference1),
+                    reference2: p.expected.reference2.as_deref(),
+                    expected_amount: Some(&p.expected.amount),
+                    paid_amount: Some(&p.transaction.amount),
+                    transaction_id: Some(&p.transaction.transaction_id),
+                    transaction_date_time: Some(&p.transaction.transaction_date_and_time),
+                })?;
+            }
+        }
+        for e in &self.missing {
+            writer.serialize(ReconciliationRow {
+                status: "missing",
+                reference1: Some(&e.reference1),
+                reference2: e.reference2.as_deref(),
+                expected_amount: Some(&e.amount),
+                paid_amount: None,
+                transaction_id: None,
+                transaction_date_time: None,
+            })?;
+        }
+        for t in &self.unexpected {
+            writer.serialize(ReconciliationRow {
+                status: "unexpected",
+                reference1: t.bill_payment_ref1.as_deref(),
+                reference2: t.bill_payment_ref2.as_deref(),
+                expected_amount: None,
+                paid_amount: Some(&t.amount),
+                transaction_id: Some(&t.transaction_id),
+                transaction_date_time: Some(&t.transaction_date_and_time),
+            })?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn to_csv(&self) -> Result<String, csv::Error> {
+        let mut bytes = Vec::new();
+        self.write_csv(&mut bytes)?;
+        Ok(String::from_utf8_lossy(&bytes).to_string())
+    }
+}
+
+// Match expected payments against bill payment inquiry results by ref1 (and ref2 when expected),
+// preferring a transaction with the expected amount over the first one with the references.
+// Each transaction pays at most one expected payment, reversed transactions are ignored.
+pub fn reconcile(
+    expected: &[ExpectedPayment],
+    transactions: &[BillPaymentTransaction],
+) -> ReconciliationReport {
+    let mut remaining: Vec<&BillPaymentTransaction> = transactions
+        .iter()
+        .filter(|t| t.reverse_flag.as_deref() != Some("Y"))
+        .collect();
+    let mut report = ReconciliationReport::default();
+
+    for payment in expected {
+        let position = remaining
+            .iter()
+            .position(|t| references_match(payment, t) && amount_matches(payment, t))
+            .or_else(|| remaining.iter().position(|t| references_match(payment, t)));
+        let Some(position) = position else {
+            report.missing.push(payment.clone());
+            continue;
+        };
+        let transaction = remaining.remove(position);
+        let reconciled = ReconciledPayment {
+            expected: payment.clone(),
+            transaction: transaction.clone(),
+        };
+        if amount_matches(payment, transaction) {
+            report.matched.push(reconciled);
+        } else {
+            report.amount_mismatch.push(reconciled);
+        }
+    }
+    report.unexpected = remaining.into_iter().cloned().collect();
+    report
+}
+
+fn references_match(payment: &ExpectedPayment, transaction: &BillPaymentTransaction) -> bool {
+    transaction.bill_payment_ref1.as_deref() == Some(payment.reference1.as_str())
+        && match &payment.reference2 {
+            Some(ref2) => transaction.bill_payment_ref2.as_deref() == Some(ref2.as_str()),
+            None => true,
+        }
+}
+
+fn amount_matches(payment: &ExpectedPayment, transaction: &BillPaymentTransaction) -> bool {
+    let expected = parse_satang(&payment.amount);
+    expected.is_some() && expected == parse_satang(&transaction.amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(id: &str, ref1: &str, amount: &str) -> BillPaymentTransaction {
+        serde_json::from_value(serde_json::json!({
+            "transactionId": id,
+            "transactionDateandTime": "2023-01-01T12:00:00+07:00",
+            "amount": amount,
+            "billPaymentRef1": ref1,
+            "billPaymentRef2": "CUST1"
+        }))
+        .unwrap()
+    }
+
+    fn expected(ref1: &str, amount: &str) -> ExpectedPayment {
+        ExpectedPayment {
+            reference1: ref1.to_string(),
+            reference2: None,
+            amount: amount.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let report = reconcile(
+            &[
+                expected("ORDER1", "100.00"),
+                expected("ORDER2", "50"),
+                expected("ORDER3", "10"),
+            ],
+            &[
+                transaction("T1", "ORDER1", "100"),
+                transaction("T2", "ORDER2", "49.50"),
+                transaction("T3", "ORDER1", "100"),
+                transaction("T4", "OTHER", "20"),
+            ],
+        );
+        assert_eq!(report.matched.len(), 1);
+        assert_eq!(report.matched[0].transaction.transaction_id, "T1");
+        assert_eq!(report.amount_mismatch.len(), 1);
+        assert_eq!(report.missing, vec![expected("ORDER3", "10")]);
+        let unexpected: Vec<&str> = report
+            .unexpected
+            .iter()
+            .map(|t| t.transaction_id.as_str())
+            .collect();
+        assert_eq!(unexpected, vec!["T3", "T4"]);
+        assert!(!report.is_balanced());
+
+        let csv = report.to_csv().unwrap();
+        assert!(csv.starts_with("status,reference1,reference2,expected_amount,paid_amount"));
+        assert_eq!(csv.lines().count(), 6);
+        assert!(report.to_json().unwrap().contains("\"amount_mismatch\""));
+    }
+
+    #[test]
+    fn test_reconcile_prefers_expected_amount() {
+        // A wrong partial payment followed by the correct one
+        let report = reconcile(
+            &[expected("ORDER1", "100.00")],
+            &[
+                transaction("T1", "ORDER1", "10"),
+                transaction("T2", "ORDER1", "100"),
+            ],
+        );
+        assert_eq!(report.matched.len(), 1);
+        assert_eq!(report.matched[0].transaction.transaction_id, "T2");
+        assert!(report.amount_mismatch.is_empty());
+        assert_eq!(report.unexpected[0].transaction_id, "T1");
+    }
+}
@@ -0,0 +1,270 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+const BASE36_DIGITS: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+// ref1, ref2 and ref3 are up to 20 characters
+pub const MAX_REFERENCE_LENGTH: usize = 20;
+// Milliseconds since the Unix epoch in base36, fixed width until year 2059
+const TIMESTAMP_LENGTH: usize = 8;
+const DEFAULT_RANDOM_LENGTH: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReferenceError {
+    // Order id is not English capital letters and numbers only
+    InvalidOrderId(String),
+    // Order id does not fit in the length left by prefix, timestamp, random part and check digit
+    TooLong { length: usize, max: usize },
+    // Reference was not generated with this generator
+    InvalidReference(String),
+    CheckDigitMismatch(String),
+}
+
+impl fmt::Display for ReferenceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferenceError::InvalidOrderId(id) => write!(f, "invalid order id {}", id),
+            ReferenceError::TooLong { length, max } => {
+                write!(f, "order id of {} characters, at most {} fit", length, max)
+            }
+            ReferenceError::InvalidReference(r) => write!(f, "invalid reference {}", r),
+            ReferenceError::CheckDigitMismatch(r) => write!(f, "check digit mismatch of {}", r),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedReference {
+    pub timestamp: DateTime<Utc>,
+    // Order id embedded with generate_for_order
+    pub order_id: Option<String>,
+}
+
+// Generates AZ09 references laid out as
+// [prefix][timestamp, 8][random][order id][check digit, 1]
+// so references sort by creation time and can be parsed back into the order id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceGenerator {
+    prefix: String,
+    random_length: usize,
+    check_digit: bool,
+}
+
+impl ReferenceGenerator {
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    // Longest order id generate_for_order accepts
+    pub fn max_order_id_length(&self) -> usize {
+        MAX_REFERENCE_LENGTH - self.fixed_length()
+    }
+
+    pub fn generate(&self) -> String {
+        self.generate_at(Utc::now(), "")
+    }
+
+    pub fn generate_for_order(&self, order_id: &str) -> Result<String, ReferenceError> {
+        if !order_id
+            .chars()
+            .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase())
+        {
+            return Err(ReferenceError::InvalidOrderId(order_id.to_string()));
+        }
+        if order_id.len() > self.max_order_id_length() {
+            return Err(ReferenceError::TooLong {
+                length: order_id.len(),
+                max: self.max_order_id_length(),
+            });
+        }
+        Ok(self.generate_at(Utc::now(), order_id))
+    }
+
+    pub fn parse(&self, reference: &str) -> Result<ParsedReference, ReferenceError> {
+        let invalid = || ReferenceError::InvalidReference(reference.to_string());
+        if reference.len() < self.fixed_length() || !reference.is_ascii() {
+            return Err(invalid());
+        }
+        let mut body = reference;
+        if self.check_digit {
+            let (head, check) = reference.split_at(reference.len() - 1);
+            if luhn_mod36_check_digit(head) != check.chars().next() {
+                return Err(ReferenceError::CheckDigitMismatch(reference.to_string()));
+            }
+            body = head;
+        }
+        let body = body
+            .strip_prefix(self.prefix.as_str())
+            .ok_or_else(invalid)?;
+        let (timestamp, rest) = body.split_at(TIMESTAMP_LENGTH);
+        let order_id = &rest[self.random_length..];
+        let millis = i64::from_str_radix(timestamp, 36).map_err(|_| invalid())?;
+        let timestamp = DateTime::from_timestamp_millis(millis).ok_or_else(invalid)?;
+        Ok(ParsedReference {
+            timestamp,
+            order_id: (!order_id.is_empty()).then(|| order_id.to_string()),
+        })
+    }
+
+    fn fixed_length(&self) -> usize {
+        self.prefix.len() + TIMESTAMP_LENGTH + self.random_length + usize::from(self.check_digit)
+    }
+
+    fn generate_at(&self, now: DateTime<Utc>, order_id: &str) -> String {
+        let mut reference = self.prefix.clone();
+        reference.push_str(&to_base36(
+            now.timestamp_millis().max(0) as u128,
+            TIMESTAMP_LENGTH,
+        ));
+        reference.push_str(&to_base36(Uuid::new_v4().as_u128(), self.random_length));
+        reference.push_str(order_id);
+        if self.check_digit {
+            // prefix and order id are validated as AZ09
+            reference.push(luhn_mod36_check_digit(&reference).unwrap());
+        }
+        reference
+    }
+}
+
+pub struct ReferenceGeneratorBuilder {
+    generator: ReferenceGenerator,
+}
+
+impl ReferenceGeneratorBuilder {
+    pub fn new() -> Self {
+        ReferenceGeneratorBuilder {
+            generator: ReferenceGenerator {
+                prefix: String::new(),
+                random_length: DEFAULT_RANDOM_LENGTH,
+                check_digit: false,
+            },
+        }
+    }
+    // e.g. the Reference 3 Prefix of the merchant profile
+    pub fn add_prefix(&mut self, prefix: &str) -> &mut Self {
+        self.generator.prefix = prefix.to_string();
+        self
+    }
+    // Random characters after the timestamp, 4 gives 1.6M references per millisecond
+    pub fn add_random_length(&mut self, random_length: usize) -> &mut Self {
+        self.generator.random_length = random_length;
+        self
+    }
+    pub fn add_check_digit(&mut self) -> &mut Self {
+        self.generator.check_digit = true;
+        self
+    }
+
+    pub fn build(&self) -> Result<ReferenceGenerator, &'static str> {
+        let generator = self.generator.clone();
+        if !generator
+            .prefix
+            .chars()
+            .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase())
+        {
+            return Err("prefix must be AZ09");
+        }
+        if generator.fixed_length() > MAX_REFERENCE_LENGTH {
+            return Err("prefix and random part are longer than 20 characters");
+        }
+        Ok(generator)
+    }
+}
+
+impl Default for ReferenceGeneratorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Lowest `length` base36 digits of value, zero padded
+fn to_base36(mut value: u128, length: usize) -> String {
+    let mut digits = vec![b'0'; length];
+    for digit in digits.iter_mut().rev() {
+        *digit = BASE36_DIGITS[(value % 36) as usize];
+        value /= 36;
+    }
+    String::from_utf8(digits).unwrap()
+}
+
+// Luhn mod N check character over base36, catches any single character error
+// and most transpositions of adjacent characters
+fn luhn_mod36_check_digit(value: &str) -> Option<char> {
+    let mut sum = 0;
+    for (i, c) in value.chars().rev().enumerate() {
+        let mut code = c.to_digit(36)?;
+        if i % 2 == 0 {
+            code *= 2;
+            code = code / 36 + code % 36;
+        }
+        sum += code;
+    }
+    Some(BASE36_DIGITS[((36 - sum % 36) % 36) as usize] as char)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_generate_reference() {
+        let generator = ReferenceGeneratorBuilder::new().build().unwrap();
+        let first = generator.generate_at(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(), "");
+        let second = generator.generate_at(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 1).unwrap(), "");
+        assert_eq!(first.len(), 12);
+        assert!(first
+            .chars()
+            .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase()));
+        assert!(first < second);
+        assert_ne!(generator.generate(), generator.generate());
+    }
+
+    #[test]
+    fn test_parse_order_id() {
+        let generator = ReferenceGeneratorBuilder::new()
+            .add_prefix("SCB")
+            .add_check_digit()
+            .build()
+            .unwrap();
+        assert_eq!(generator.max_order_id_length(), 4);
+        let reference = generator.generate_for_order("A12").unwrap();
+        assert_eq!(reference.len(), 19);
+        assert!(reference.starts_with("SCB"));
+        let parsed = generator.parse(&reference).unwrap();
+        assert_eq!(parsed.order_id.as_deref(), Some("A12"));
+        assert!((Utc::now() - parsed.timestamp).num_seconds() < 5);
+
+        assert_eq!(
+            generator.generate_for_order("ORDER1"),
+            Err(ReferenceError::TooLong { length: 6, max: 4 })
+        );
+        assert!(matches!(
+            generator.generate_for_order("a1"),
+            Err(ReferenceError::InvalidOrderId(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_digit() {
+        let generator = ReferenceGeneratorBuilder::new()
+            .add_check_digit()
+            .build()
+            .unwrap();
+        let reference = generator.generate_for_order("ORDER1").unwrap();
+        let mut corrupted: Vec<char> = reference.chars().collect();
+        corrupted[14] = if corrupted[14] == 'Z' { 'Y' } else { 'Z' };
+        let corrupted: String = corrupted.into_iter().collect();
+        assert_eq!(
+            generator.parse(&corrupted),
+            Err(ReferenceError::CheckDigitMismatch(corrupted.clone()))
+        );
+        assert!(ReferenceGeneratorBuilder::new()
+            .add_prefix("VERYLONGPREFIX")
+            .build()
+            .is_err());
+    }
+}
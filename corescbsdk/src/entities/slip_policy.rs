@@ -0,0 +1,309 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::entities::bill_pay::BillPaymentTransactionSlip;
+use crate::entities::validate::parse_satang;
+
+// Slip times are local time in Thailand (UTC+7)
+pub(crate) const BANGKOK_OFFSET_SECONDS: i32 = 7 * 3600;
+// Slips up to this many minutes in the future are accepted to tolerate clock differences
+const ALLOWED_CLOCK_SKEW_MINUTES: i64 = 5;
+// ISO4217 numeric code of Thai Baht
+const THB_NUMERIC_CODE: &str = "764";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "rule", content = "expected", rename_all = "snake_case")]
+pub enum SlipRule {
+    // receiver.proxy.value equals e.g. our biller id
+    ReceiverProxy(String),
+    // receiver.account.value equals our account number
+    ReceiverAccount(String),
+    // amount equals the order total, compared as satang
+    Amount(String),
+    Ref1(String),
+    Ref2(String),
+    Ref3(String),
+    // transDate + transTime is no older than N minutes
+    MaxAgeMinutes(i64),
+    // paidLocalCurrency equals e.g. THB or 764
+    Currency(String),
+}
+
+impl fmt::Display for SlipRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SlipRule::ReceiverProxy(_) => write!(f, "receiver_proxy"),
+            SlipRule::ReceiverAccount(_) => write!(f, "receiver_account"),
+            SlipRule::Amount(_) => write!(f, "amount"),
+            SlipRule::Ref1(_) => write!(f, "ref1"),
+            SlipRule::Ref2(_) => write!(f, "ref2"),
+            SlipRule::Ref3(_) => write!(f, "ref3"),
+            SlipRule::MaxAgeMinutes(_) => write!(f, "max_age_minutes"),
+            SlipRule::Currency(_) => write!(f, "currency"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleResult {
+    pub rule: SlipRule,
+    pub passed: bool,
+    // Value found on the slip
+    pub actual: String,
+    // Reason of the failure
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlipPolicyReport {
+    // Transaction Slip ID
+    pub trans_ref: String,
+    // One result per rule of the policy, in the order of the policy
+    pub results: Vec<RuleResult>,
+}
+
+impl SlipPolicyReport {
+    pub fn is_accepted(&self) -> bool {
+        self.results.iter().all(|r| r.passed)
+    }
+
+    pub fn failures(&self) -> Vec<&RuleResult> {
+        self.results.iter().filter(|r| !r.passed).collect()
+    }
+}
+
+// Set of rules a slip has to satisfy to be accepted, evaluated with `evaluate`.
+// The policy is serializable so it can be kept in configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlipPolicy {
+    pub rules: Vec<SlipRule>,
+}
+
+impl SlipPolicy {
+    pub fn evaluate(&self, slip: &BillPaymentTransactionSlip) -> SlipPolicyReport {
+        self.evaluate_at(slip, Utc::now())
+    }
+
+    pub fn evaluate_at(
+        &self,
+        slip: &BillPaymentTransactionSlip,
+        now: DateTime<Utc>,
+    ) -> SlipPolicyReport {
+        SlipPolicyReport {
+            trans_ref: slip.trans_ref.to_string(),
+            results: self
+                .rules
+                .iter()
+                .map(|rule| evaluate_rule(rule, slip, now))
+                .collect(),
+        }
+    }
+}
+
+pub struct SlipPolicyBuilder {
+    policy: SlipPolicy,
+}
+
+impl SlipPolicyBuilder {
+    pub fn new() -> Self {
+        SlipPolicyBuilder {
+            policy: SlipPolicy::default(),
+        }
+    }
+    pub fn receiver_proxy(&mut self, proxy_value: &str) -> &mut Self {
+        self.add_rule(SlipRule::ReceiverProxy(proxy_value.to_string()))
+    }
+    pub fn receiver_account(&mut self, account_value: &str) -> &mut Self {
+        self.add_rule(SlipRule::ReceiverAccount(account_value.to_string()))
+    }
+    pub fn amount(&mut self, amount: &str) -> &mut Self {
+        self.add_rule(SlipRule::Amount(amount.to_string()))
+    }
+    pub fn ref1(&mut self, ref1: &str) -> &mut Self {
+        self.add_rule(SlipRule::Ref1(ref1.to_string()))
+    }
+    pub fn ref2(&mut self, ref2: &str) -> &mut Self {
+        self.add_rule(SlipRule::Ref2(ref2.to_string()))
+    }
+    pub fn ref3(&mut self, ref3: &str) -> &mut Self {
+        self.add_rule(SlipRule::Ref3(ref3.to_string()))
+    }
+    pub fn max_age_minutes(&mut self, minutes: i64) -> &mut Self {
+        self.add_rule(SlipRule::MaxAgeMinutes(minutes))
+    }
+    pub fn currency(&mut self, currency: &str) -> &mut Self {
+        self.add_rule(SlipRule::Currency(currency.to_string()))
+    }
+    pub fn add_rule(&mut self, rule: SlipRule) -> &mut Self {
+        self.policy.rules.push(rule);
+        self
+    }
+
+    pub fn build(&self) -> SlipPolicy {
+        self.policy.clone()
+    }
+}
+
+impl Default for SlipPolicyBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn evaluate_rule(
+    rule: &SlipRule,
+    slip: &BillPaymentTransactionSlip,
+    now: DateTime<Utc>,
+) -> RuleResult {
+    let (actual, failure) = match rule {
+        SlipRule::ReceiverProxy(expected) => equals(expected, &slip.receiver.proxy.value),
+        SlipRule::ReceiverAccount(expected) => equals(expected, &slip.receiver.account.value),
+        SlipRule::Ref1(expected) => equals(expected, &slip.ref1),
+        SlipRule::Ref2(expected) => equals(expected, &slip.ref2),
+        SlipRule::Ref3(expected) => equals(expected, &slip.ref3),
+        SlipRule::Amount(expected) => {
+            let actual = slip.amount.to_string();
+            let failure = match (parse_satang(expected), parse_satang(&slip.amount)) {
+                (Some(e), Some(a)) if e == a => None,
+                (Some(_), Some(_)) => Some(format!("expected {} but was {}", expected, actual)),
+                (None, _) => Some(format!("invalid expected amount {}", expected)),
+                (_, None) => Some(format!("invalid amount {} on slip", actual)),
+            };
+            (actual, failure)
+        }
+        SlipRule::Currency(expected) => {
+            let actual = slip.paid_local_currency.to_string();
+            let failure = if normalize_currency(expected) == normalize_currency(&actual) {
+                None
+            } else {
+                Some(format!("expected {} but was {}", expected, actual))
+            };
+            (actual, failure)
+        }
+        SlipRule::MaxAgeMinutes(max_age) => {
+            let actual = format!("{} {}", slip.trans_date, slip.trans_time);
+            let failure = match parse_slip_time(&slip.trans_date, &slip.trans_time) {
+                Some(time) => {
+                    let age = (now - time).num_minutes();
+                    if age > *max_age {
+                        Some(format!("slip is {} minutes old, limit is {}", age, max_age))
+                    } else if age < -ALLOWED_CLOCK_SKEW_MINUTES {
+                        Some(format!("slip time is {} minutes in the future", -age))
+                    } else {
+                        None
+                    }
+                }
+                None => Some(format!("invalid transaction time {}", actual)),
+            };
+            (actual, failure)
+        }
+    };
+    RuleResult {
+        rule: rule.clone(),
+        passed: failure.is_none(),
+        actual,
+        message: failure,
+    }
+}
+
+fn equals(expected: &str, actual: &str) -> (String, Option<String>) {
+    let failure = if expected.trim() == actual.trim() {
+        None
+    } else {
+        Some(format!("expected {} but was {}", expected, actual))
+    };
+    (actual.to_string(), failure)
+}
+
+fn normalize_currency(currency: &str) -> String {
+    let currency = currency.trim().to_uppercase();
+    if currency == "THB" {
+        return THB_NUMERIC_CODE.to_string();
+    }
+    currency
+}
+
+// transDate is yyyyMMdd or yyyy-MM-dd, transTime is HH:mm:ss
+fn parse_slip_time(trans_date: &str, trans_time: &str) -> Option<DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(trans_date, "%Y%m%d")
+        .or_else(|_| NaiveDate::parse_from_str(trans_date, "%Y-%m-%d"))
+        .ok()?;
+    let time = NaiveTime::parse_from_str(trans_time, "%H:%M:%S").ok()?;
+    let offset = FixedOffset::east_opt(BANGKOK_OFFSET_SECONDS)?;
+    offset
+        .from_local_datetime(&NaiveDateTime::new(date, time))
+        .single()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slip() -> BillPaymentTransactionSlip {
+        serde_json::from_value(serde_json::json!({
+            "transRef": "2023010112100000000000000",
+            "sendingBank": "004",
+            "receivingBank": "014",
+            "transDate": "20230101",
+            "transTime": "12:10:00",
+            "sender": {
+                "displayName": "MR. A",
+                "name": "MR. A",
+                "proxy": {"type": "", "value": ""},
+                "account": {"type": "BANKAC", "value": "xxx-x-x1234-x"}
+            },
+            "receiver": {
+                "displayName": "SHOP",
+                "name": "SHOP",
+                "proxy": {"type": "BILLERID", "value": "311040039475180"},
+                "account": {"type": "BANKAC", "value": "xxx-x-x5678-x"}
+            },
+            "amount": "100.5",
+            "paidLocalAmount": "100.5",
+            "paidLocalCurrency": "764",
+            "countryCode": "TH",
+            "ref1": "ORDER1",
+            "ref2": "CUST1",
+            "ref3": "SCB1"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_accepted_slip() {
+        let policy = SlipPolicyBuilder::new()
+            .receiver_proxy("311040039475180")
+            .amount("100.50")
+            .ref1("ORDER1")
+            .ref2("CUST1")
+            .max_age_minutes(30)
+            .currency("THB")
+            .build();
+        let now = Utc.with_ymd_and_hms(2023, 1, 1, 5, 30, 0).unwrap();
+        let report = policy.evaluate_at(&slip(), now);
+        assert!(report.is_accepted(), "{:?}", report.failures());
+        assert_eq!(report.results.len(), 6);
+    }
+
+    #[test]
+    fn test_rejected_slip() {
+        let policy = SlipPolicyBuilder::new()
+            .amount("100")
+            .ref1("ORDER2")
+            .max_age_minutes(10)
+            .build();
+        let now = Utc.with_ymd_and_hms(2023, 1, 1, 5, 30, 0).unwrap();
+        let report = policy.evaluate_at(&slip(), now);
+        assert!(!report.is_accepted());
+        let failed: Vec<String> = report
+            .failures()
+            .iter()
+            .map(|r| r.rule.to_string())
+            .collect();
+        assert_eq!(failed, vec!["amount", "ref1", "max_age_minutes"]);
+    }
+}
@@ -0,0 +1,128 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+use serde::{Deserialize, Serialize};
+
+use crate::entities::bank_code::BankCode;
+use crate::entities::emv::{find_tag, parse_tlv, verify_crc, EmvError};
+
+// Tags of the mini QR printed on Thai bank transfer slips, e.g.
+// 0041000600000101030140220{transRef}5102TH9104{CRC}
+const TAG_MERCHANT_PRESENTED: &str = "00";
+const TAG_API_ID: &str = "00";
+const TAG_SENDING_BANK: &str = "01";
+const TAG_TRANS_REF: &str = "02";
+const TAG_COUNTRY_CODE: &str = "51";
+const TAG_CRC: &str = "91";
+
+// API ID of the slip verification API
+pub const SLIP_VERIFICATION_API_ID: &str = "000001";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlipQRCode {
+    // API ID, 000001 for slip verification
+    pub api_id: String,
+    // Sender Bank Code, e.g. 014
+    pub sending_bank: BankCode,
+    // Transaction Slip ID
+    pub trans_ref: String,
+    // Country Code, e.g. TH
+    pub country_code: String,
+    // CRC-16 of the payload, 4 hex digits
+    pub crc: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlipQRError {
+    // Payload is not a valid TLV or its CRC does not match
+    InvalidPayload(EmvError),
+    // Required data object is not present
+    MissingField(&'static str),
+    // QR is valid but not a slip verification QR
+    UnsupportedApiId(String),
+}
+
+impl fmt::Display for SlipQRError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SlipQRError::InvalidPayload(e) => write!(f, "invalid slip QR: {}", e),
+            SlipQRError::MissingField(field) => write!(f, "slip QR has no {}", field),
+            SlipQRError::UnsupportedApiId(id) => write!(f, "unsupported slip QR API ID {}", id),
+        }
+    }
+}
+
+impl SlipQRCode {
+    pub fn parse(payload: &str) -> Result<Self, SlipQRError> {
+        let payload = payload.trim();
+        verify_crc(payload, TAG_CRC).map_err(SlipQRError::InvalidPayload)?;
+        let objects = parse_tlv(payload).map_err(SlipQRError::InvalidPayload)?;
+
+        let template = find_tag(&objects, TAG_MERCHANT_PRESENTED)
+            .ok_or(SlipQRError::MissingField("merchant presented data"))?;
+        let nested = parse_tlv(template).map_err(SlipQRError::InvalidPayload)?;
+
+        let api_id = find_tag(&nested, TAG_API_ID).ok_or(SlipQRError::MissingField("API ID"))?;
+        if api_id != SLIP_VERIFICATION_API_ID {
+            return Err(SlipQRError::UnsupportedApiId(api_id.to_string()));
+        }
+        let sending_bank =
+            find_tag(&nested, TAG_SENDING_BANK).ok_or(SlipQRError::MissingField("sending bank"))?;
+        let trans_ref =
+            find_tag(&nested, TAG_TRANS_REF).ok_or(SlipQRError::MissingField("trans ref"))?;
+        let country_code = find_tag(&objects, TAG_COUNTRY_CODE)
+            .ok_or(SlipQRError::MissingField("country code"))?;
+        let crc = find_tag(&objects, TAG_CRC).ok_or(SlipQRError::MissingField("CRC"))?;
+
+        Ok(SlipQRCode {
+            api_id: api_id.to_string(),
+            sending_bank: BankCode::from_code(sending_bank),
+            trans_ref: trans_ref.to_string(),
+            country_code: country_code.to_string(),
+            crc: crc.to_uppercase(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::bank_code::BankCode;
+    use crate::entities::emv::crc16_ccitt;
+
+    fn with_crc(body: &str) -> String {
+        format!("{}{:04X}", body, crc16_ccitt(body.as_bytes()))
+    }
+
+    #[test]
+    fn test_parse_slip_qr() {
+        let payload = with_crc("004600060000010103014022520230101121000000000000005102TH9104");
+        let slip = SlipQRCode::parse(&payload).unwrap();
+        assert_eq!(slip.api_id, "000001");
+        assert_eq!(slip.sending_bank, BankCode::SCB);
+        assert_eq!(slip.trans_ref, "2023010112100000000000000");
+        assert_eq!(slip.country_code, "TH");
+    }
+
+    #[test]
+    fn test_parse_slip_qr_errors() {
+        let payload = with_crc("004600060000010103014022520230101121000000000000005102TH9104");
+        let tampered = payload.replace("014", "004");
+        assert!(matches!(
+            SlipQRCode::parse(&tampered),
+            Err(SlipQRError::InvalidPayload(EmvError::CrcMismatch { .. }))
+        ));
+
+        let payload = with_crc("004600060000010103014022520230101121000000000000009104");
+        assert_eq!(
+            SlipQRCode::parse(&payload),
+            Err(SlipQRError::MissingField("country code"))
+        );
+
+        let payload = with_crc("004600060000020103014022520230101121000000000000005102TH9104");
+        assert_eq!(
+            SlipQRCode::parse(&payload),
+            Err(SlipQRError::UnsupportedApiId("000002".to_string()))
+        );
+    }
+}
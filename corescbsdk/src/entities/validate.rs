@@ -36,6 +36,25 @@ pub fn validate_data_type_date(data: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+// "100", "100.5" and "100.50" are all 10050 satang
+pub(crate) fn parse_satang(amount: &str) -> Option<i64> {
+    let amount = amount.trim();
+    let (baht, satang) = match amount.split_once('.') {
+        Some((baht, satang)) => (baht, satang),
+        None => (amount, ""),
+    };
+    if baht.is_empty()
+        || satang.len() > 2
+        || !baht.chars().all(|c| c.is_ascii_digit())
+        || !satang.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let baht: i64 = baht.parse().ok()?;
+    let satang: i64 = format!("{:0<2}", satang).parse().ok()?;
+    baht.checked_mul(100)?.checked_add(satang)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -61,4 +80,13 @@ mod tests {
             Err(ValidationError::new("invalid_format"))
         );
     }
+
+    #[test]
+    fn test_parse_satang() {
+        assert_eq!(parse_satang("100"), Some(10000));
+        assert_eq!(parse_satang("100.5"), Some(10050));
+        assert_eq!(parse_satang("100.05"), Some(10005));
+        assert_eq!(parse_satang("100.055"), None);
+        assert_eq!(parse_satang("-1"), None);
+    }
 }
@@ -1 +1 @@
-pub(crate) mod scb_error;
+pub mod scb_error;
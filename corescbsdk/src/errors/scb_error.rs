@@ -1,9 +1,47 @@
 use std::fmt;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub enum SCBAPIError {
     HttpRequestError(reqwest::Error),
     SCBError(String),
+    AuthorizationError(String),
+    // The slip was already accepted for another order
+    DuplicateSlip {
+        trans_ref: String,
+        order_id: String,
+    },
+    StorageError(String),
+    // Rejected by the client side rate limiter, retry after the given duration
+    RateLimited {
+        endpoint: String,
+        retry_after: Duration,
+    },
+    // The circuit breaker of the endpoint is open, SCB was not called
+    CircuitOpen {
+        endpoint: String,
+        retry_after: Duration,
+    },
+    // 5xx status from the SCB gateway
+    ServerError {
+        status: u16,
+        message: String,
+    },
+    // Body which is not an SCB response, e.g. the HTML error page of a proxy
+    InvalidResponse(String),
+}
+
+impl SCBAPIError {
+    // Failures of the SCB gateway or the network rather than errors returned by SCB,
+    // counted by the circuit breaker
+    pub fn is_gateway_failure(&self) -> bool {
+        matches!(
+            self,
+            SCBAPIError::HttpRequestError(_)
+                | SCBAPIError::ServerError { .. }
+                | SCBAPIError::InvalidResponse(_)
+        )
+    }
 }
 impl From<reqwest::Error> for SCBAPIError {
     fn from(error: reqwest::Error) -> Self {
@@ -21,6 +59,36 @@ impl fmt::Display for SCBAPIError {
         match self {
             SCBAPIError::HttpRequestError(e) => write!(f, "HTTP Request Error: {}", e),
             SCBAPIError::SCBError(e) => write!(f, "SCB Error: {}", e),
+            SCBAPIError::AuthorizationError(e) => write!(f, "Authorization Error: {}", e),
+            SCBAPIError::DuplicateSlip {
+                trans_ref,
+                order_id,
+            } => write!(
+                f,
+                "Duplicate Slip: {} was already used for order {}",
+                trans_ref, order_id
+            ),
+            SCBAPIError::StorageError(e) => write!(f, "Storage Error: {}", e),
+            SCBAPIError::RateLimited {
+                endpoint,
+                retry_after,
+            } => write!(
+                f,
+                "Rate Limited: {} retry after {:?}",
+                endpoint, retry_after
+            ),
+            SCBAPIError::CircuitOpen {
+                endpoint,
+                retry_after,
+            } => write!(
+                f,
+                "Circuit Open: {} retry after {:?}",
+                endpoint, retry_after
+            ),
+            SCBAPIError::ServerError { status, message } => {
+                write!(f, "Server Error: {} {}", status, message)
+            }
+            SCBAPIError::InvalidResponse(e) => write!(f, "Invalid Response: {}", e),
         }
     }
 }
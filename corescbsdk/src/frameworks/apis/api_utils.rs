@@ -1,28 +1,46 @@
-use log::debug;
 use reqwest::header::{HeaderValue, ACCEPT_LANGUAGE, AUTHORIZATION, CONTENT_TYPE, USER_AGENT};
 use reqwest::Response;
 use serde::de::DeserializeOwned;
+use tracing::{debug, Span};
 use uuid::Uuid;
 
 use crate::entities::base::{AccessToken, SCBResponse};
 use crate::errors::scb_error::SCBAPIError;
+#[cfg(feature = "metrics")]
+use crate::frameworks::apis::telemetry;
 
 pub const OAUTH_TOKEN_V1_URL: &str = "/v1/oauth/token";
+pub const OAUTH_TOKEN_REFRESH_V1_URL: &str = "/v1/oauth/token/refresh";
+pub const OAUTH_AUTHORIZE_V2_URL: &str = "/v2/oauth/authorize";
 pub const QRCODE_CREATE_V1_URL: &str = "/v1/payment/qrcode/create";
+pub const QRCODE_CREDIT_CARD_V1_URL: &str = "/v1/payment/qrcode/creditcard";
 pub const BILL_PAYMENT_TRANSACTION_V1_URL: &str = "/v1/payment/billpayment/transactions";
 pub const INQUIRY_BILL_PAYMENT_TRANSACTION_V1_URL: &str = "/v1/payment/billpayment/inquiry";
+pub const B_SCAN_C_PAYMENT_V1_URL: &str = "/v1/payment/bscanc/payment";
+pub const B_SCAN_C_VOID_V1_URL: &str = "/v1/payment/bscanc/void";
+pub const CUSTOMER_PROFILE_V2_URL: &str = "/v2/customers/profile";
 
 const BASE_URL: &str = "https://api-sandbox.partners.scb/partners/sandbox";
 
+// Characters of a 5xx body kept in SCBAPIError::ServerError
+const MAX_ERROR_BODY_LENGTH: usize = 200;
+
 pub fn api_url(path: &str) -> String {
     format!("{}{}", BASE_URL, path)
 }
 pub fn generate_header(
-    resource_owner_id: &String,
+    resource_owner_id: &str,
+    access_token: &Option<AccessToken>,
+) -> reqwest::header::HeaderMap {
+    generate_header_with_request_uid(resource_owner_id, access_token, &Uuid::new_v4().to_string())
+}
+// Same as generate_header with a requestUId chosen by the caller, e.g. to store it with the payment
+pub fn generate_header_with_request_uid(
+    resource_owner_id: &str,
     access_token: &Option<AccessToken>,
+    request_uid: &str,
 ) -> reqwest::header::HeaderMap {
     let mut headers = reqwest::header::HeaderMap::new();
-    let request_uid = Uuid::new_v4();
 
     debug!("generate header");
 
@@ -33,33 +51,103 @@ pub fn generate_header(
         "resourceOwnerId",
         HeaderValue::from_str(resource_owner_id).unwrap(),
     );
-    headers.insert(
-        "requestUId",
-        HeaderValue::from_str(&request_uid.to_string()).unwrap(),
-    );
+    headers.insert("requestUId", HeaderValue::from_str(request_uid).unwrap());
     if let Some(token) = access_token {
         let token = format!("Bearer {}", token.access_token);
         headers.insert(AUTHORIZATION, HeaderValue::from_str(&token).unwrap());
     }
+    Span::current().record("request_uid", request_uid);
+    #[cfg(feature = "opentelemetry")]
+    inject_trace_context(&mut headers);
     headers
 }
+
+// Add the headers of the global OpenTelemetry propagator, e.g. traceparent,
+// for the context of the current span
+#[cfg(feature = "opentelemetry")]
+fn inject_trace_context(headers: &mut reqwest::header::HeaderMap) {
+    use opentelemetry::propagation::Injector;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    struct HeaderInjector<'a>(&'a mut reqwest::header::HeaderMap);
+
+    impl Injector for HeaderInjector<'_> {
+        fn set(&mut self, key: &str, value: String) {
+            if let (Ok(name), Ok(value)) = (
+                reqwest::header::HeaderName::from_bytes(key.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                self.0.insert(name, value);
+            }
+        }
+    }
+
+    let context = Span::current().context();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(headers))
+    });
+}
 pub async fn map_result<T: DeserializeOwned + std::fmt::Debug>(
     response: Result<Response, SCBAPIError>,
 ) -> Result<T, SCBAPIError> {
     match response {
         Ok(response) => {
+            let status = response.status();
+            Span::current().record("http_status", status.as_u16());
+            if status.is_server_error() {
+                let body = response.text().await.unwrap_or_default();
+                return Err(SCBAPIError::ServerError {
+                    status: status.as_u16(),
+                    message: body.chars().take(MAX_ERROR_BODY_LENGTH).collect(),
+                });
+            }
             let body = response.json::<SCBResponse<T>>().await;
             match body {
                 Ok(body) => {
                     debug!("Response: {:#?}", body);
+                    Span::current().record("scb_status", body.status.code);
+                    #[cfg(feature = "metrics")]
+                    let _ = telemetry::SCB_STATUS.try_with(|s| s.set(Some(body.status.code)));
                     if body.status.code != 1000 {
                         return Err(SCBAPIError::SCBError(body.status.description));
                     }
                     Ok(body.data.unwrap())
                 }
-                Err(e) => Err(SCBAPIError::SCBError(e.to_string())),
+                Err(e) if e.is_decode() => Err(SCBAPIError::InvalidResponse(e.to_string())),
+                Err(e) => Err(SCBAPIError::HttpRequestError(e)),
             }
         }
         Err(e) => Err(e),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, body: &str) -> Result<Response, SCBAPIError> {
+        Ok(Response::from(
+            http::Response::builder()
+                .status(status)
+                .body(body.to_string())
+                .unwrap(),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_map_result_gateway_failures() {
+        let result = map_result::<String>(response(502, "<html>Bad Gateway</html>")).await;
+        assert!(matches!(
+            result,
+            Err(SCBAPIError::ServerError { status: 502, .. })
+        ));
+        let result = map_result::<String>(response(200, "<html></html>")).await;
+        assert!(matches!(result, Err(SCBAPIError::InvalidResponse(_))));
+        let result = map_result::<String>(response(
+            401,
+            r#"{"status": {"code": 9300, "description": "Invalid token"}}"#,
+        ))
+        .await;
+        assert!(matches!(result, Err(SCBAPIError::SCBError(e)) if e == "Invalid token"));
+    }
+}
@@ -0,0 +1,352 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+
+use crate::entities::b_scan_c::{BScanCPaymentRequest, BScanCPaymentResponse, BScanCVoidResponse};
+use crate::entities::bank_code::BankCode;
+use crate::entities::base::AccessToken;
+use crate::entities::bill_pay::{
+    BillPaymentInquiryError, BillPaymentInquiryRequest, BillPaymentTransaction,
+    BillPaymentTransactionSlip,
+};
+use crate::entities::customer::CustomerProfile;
+use crate::entities::oauth::AuthorizationSession;
+use crate::entities::payment_session::{PaymentOutcome, PaymentSession};
+use crate::entities::qr_code::{
+    QRCodeCancelResponse, QRCodeRequest, QRCodeResponse, QRCreditCardTransaction,
+};
+use crate::entities::reconciliation::{ExpectedPayment, ReconciliationReport};
+use crate::entities::reference::ReferenceGenerator;
+use crate::entities::slip_qr::SlipQRCode;
+use crate::errors::scb_error::SCBAPIError;
+use crate::frameworks::apis::circuit_breaker::CircuitBreaker;
+use crate::frameworks::apis::rate_limit::RateLimiter;
+use crate::frameworks::apis::scb;
+use crate::frameworks::config::SCBConfig;
+use crate::frameworks::credentials::CredentialProvider;
+use crate::frameworks::stores::payment_store::PaymentStore;
+use crate::frameworks::stores::slip_registry::SlipRegistry;
+
+// Synchronous SCBClientAPI for code without an async runtime.
+// Runs the async client on its own current-thread runtime, so it must not be used
+// from inside an async runtime. Clones share the runtime.
+#[derive(Debug, Clone)]
+pub struct SCBClientAPI {
+    inner: scb::SCBClientAPI,
+    runtime: Arc<Runtime>,
+}
+
+fn create_runtime() -> Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to create runtime")
+}
+
+impl SCBClientAPI {
+    pub fn new(application_name: &str, application_key: &str, secret_key: &str) -> SCBClientAPI {
+        Self::from_async(scb::SCBClientAPI::new(
+            application_name,
+            application_key,
+            secret_key,
+        ))
+    }
+
+    pub fn from_config(config: &SCBConfig) -> SCBClientAPI {
+        Self::from_async(scb::SCBClientAPI::from_config(config))
+    }
+
+    pub fn from_credential_provider(
+        credential_provider: Arc<dyn CredentialProvider>,
+    ) -> Result<SCBClientAPI, SCBAPIError> {
+        scb::SCBClientAPI::from_credential_provider(credential_provider).map(Self::from_async)
+    }
+
+    pub fn from_async(inner: scb::SCBClientAPI) -> SCBClientAPI {
+        SCBClientAPI {
+            inner,
+            runtime: Arc::new(create_runtime()),
+        }
+    }
+
+    pub fn into_async(self) -> scb::SCBClientAPI {
+        self.inner
+    }
+
+    pub fn set_rate_limiter(&mut self, rate_limiter: Arc<RateLimiter>) {
+        self.inner.set_rate_limiter(rate_limiter);
+    }
+
+    pub fn set_circuit_breaker(&mut self, circuit_breaker: Arc<CircuitBreaker>) {
+        self.inner.set_circuit_breaker(circuit_breaker);
+    }
+
+    pub fn get_access_token(&mut self) -> Result<AccessToken, SCBAPIError> {
+        self.runtime.block_on(self.inner.get_access_token())
+    }
+
+    pub fn oauth_authorize(
+        &mut self,
+        end_state: &str,
+    ) -> Result<AuthorizationSession, SCBAPIError> {
+        self.runtime.block_on(self.inner.oauth_authorize(end_state))
+    }
+
+    pub fn oauth_exchange_auth_code(
+        &mut self,
+        customer_id: &String,
+        session: &AuthorizationSession,
+        auth_code: &String,
+        state: &String,
+    ) -> Result<AccessToken, SCBAPIError> {
+        self.runtime.block_on(self.inner.oauth_exchange_auth_code(
+            customer_id,
+            session,
+            auth_code,
+            state,
+        ))
+    }
+
+    pub fn get_customer_access_token(
+        &mut self,
+        customer_id: &String,
+    ) -> Result<AccessToken, SCBAPIError> {
+        self.runtime
+            .block_on(self.inner.get_customer_access_token(customer_id))
+    }
+
+    pub fn set_customer_access_token(&mut self, customer_id: &String, token: AccessToken) {
+        self.inner.set_customer_access_token(customer_id, token);
+    }
+
+    pub fn remove_customer_access_token(&mut self, customer_id: &String) -> Option<AccessToken> {
+        self.inner.remove_customer_access_token(customer_id)
+    }
+
+    pub fn get_customer_profile(
+        &mut self,
+        customer_id: &String,
+    ) -> Result<CustomerProfile, SCBAPIError> {
+        self.runtime
+            .block_on(self.inner.get_customer_profile(customer_id))
+    }
+
+    pub fn set_ref3_prefix(&mut self, ref3_prefix: &str) {
+        self.inner.set_ref3_prefix(ref3_prefix);
+    }
+
+    pub fn set_credential_provider(&mut self, credential_provider: Arc<dyn CredentialProvider>) {
+        self.inner.set_credential_provider(credential_provider);
+    }
+
+    pub fn set_biller_id(&mut self, biller_id: &str) {
+        self.inner.set_biller_id(biller_id);
+    }
+
+    pub fn biller_id(&self) -> Option<&str> {
+        self.inner.biller_id()
+    }
+
+    pub fn ref3_generator(&self) -> Result<ReferenceGenerator, &'static str> {
+        self.inner.ref3_generator()
+    }
+
+    pub fn set_payment_store(&mut self, payment_store: Arc<dyn PaymentStore>) {
+        self.inner.set_payment_store(payment_store);
+    }
+
+    pub fn qr_code_create(
+        &mut self,
+        qr_code_params: &QRCodeRequest,
+    ) -> Result<QRCodeResponse, SCBAPIError> {
+        self.runtime
+            .block_on(self.inner.qr_code_create(qr_code_params))
+    }
+
+    pub fn get_qr_cs_transaction(
+        &mut self,
+        qr_code_id: &str,
+    ) -> Result<QRCreditCardTransaction, SCBAPIError> {
+        self.runtime
+            .block_on(self.inner.get_qr_cs_transaction(qr_code_id))
+    }
+
+    pub fn qr_cs_cancel(&mut self, qr_code_id: &str) -> Result<QRCodeCancelResponse, SCBAPIError> {
+        self.runtime.block_on(self.inner.qr_cs_cancel(qr_code_id))
+    }
+
+    pub fn get_slip_verification_qr30(
+        &mut self,
+        trans_ref: &String,
+        sending_bank: &BankCode,
+    ) -> Result<BillPaymentTransactionSlip, SCBAPIError> {
+        self.runtime.block_on(
+            self.inner
+                .get_slip_verification_qr30(trans_ref, sending_bank),
+        )
+    }
+
+    pub fn set_slip_registry(&mut self, slip_registry: Arc<dyn SlipRegistry>) {
+        self.inner.set_slip_registry(slip_registry);
+    }
+
+    pub fn get_slip_verification_for_order(
+        &mut self,
+        trans_ref: &String,
+        sending_bank: &BankCode,
+        order_id: &str,
+    ) -> Result<BillPaymentTransactionSlip, SCBAPIError> {
+        self.runtime
+            .block_on(
+                self.inner
+                    .get_slip_verification_for_order(trans_ref, sending_bank, order_id),
+            )
+    }
+
+    pub fn get_slip_verification_from_qr(
+        &mut self,
+        slip_qr: &SlipQRCode,
+    ) -> Result<BillPaymentTransactionSlip, SCBAPIError> {
+        self.runtime
+            .block_on(self.inner.get_slip_verification_from_qr(slip_qr))
+    }
+
+    pub fn query_bill_payment_transaction(
+        &mut self,
+        params: &BillPaymentInquiryRequest,
+    ) -> Result<Vec<BillPaymentTransaction>, SCBAPIError> {
+        self.runtime
+            .block_on(self.inner.query_bill_payment_transaction(params))
+    }
+
+    // Transactions of these reference1 to the billers over the date range, fetched while iterating
+    pub fn query_bill_payment_transaction_iter<S: AsRef<str>, R: AsRef<str>>(
+        &mut self,
+        biller_ids: &[S],
+        references1: &[R],
+        from: NaiveDate,
+        to: NaiveDate,
+        concurrency: usize,
+    ) -> Result<BillPaymentTransactionIter, SCBAPIError> {
+        let stream = self
+            .runtime
+            .block_on(self.inner.query_bill_payment_transaction_stream(
+                biller_ids,
+                references1,
+                from,
+                to,
+                concurrency,
+            ))?;
+        Ok(BillPaymentTransactionIter {
+            stream,
+            runtime: self.runtime.clone(),
+        })
+    }
+
+    pub fn reconcile_bill_payments(
+        &mut self,
+        biller_id: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+        expected: &[ExpectedPayment],
+    ) -> Result<ReconciliationReport, SCBAPIError> {
+        self.runtime.block_on(
+            self.inner
+                .reconcile_bill_payments(biller_id, from, to, expected),
+        )
+    }
+
+    pub fn wait_for_payment(
+        &mut self,
+        biller_id: &str,
+        reference1: &str,
+        reference2: Option<&str>,
+        amount: &str,
+        deadline: DateTime<Utc>,
+    ) -> PaymentOutcome {
+        self.runtime.block_on(
+            self.inner
+                .wait_for_payment(biller_id, reference1, reference2, amount, deadline),
+        )
+    }
+
+    // Webhook notifications can be sent from any thread with mpsc::Sender::blocking_send
+    pub fn wait_for_payment_session(
+        &mut self,
+        session: &mut PaymentSession,
+        webhook: Option<&mut mpsc::Receiver<BillPaymentTransaction>>,
+    ) -> PaymentOutcome {
+        self.runtime
+            .block_on(self.inner.wait_for_payment_session(session, webhook))
+    }
+
+    pub fn b_scan_c_payment(
+        &mut self,
+        payment_request: &BScanCPaymentRequest,
+    ) -> Result<BScanCPaymentResponse, SCBAPIError> {
+        self.runtime
+            .block_on(self.inner.b_scan_c_payment(payment_request))
+    }
+
+    pub fn b_scan_c_void(
+        &mut self,
+        partner_transaction_id: &str,
+        transaction_id: Option<&str>,
+    ) -> Result<BScanCVoidResponse, SCBAPIError> {
+        self.runtime.block_on(
+            self.inner
+                .b_scan_c_void(partner_transaction_id, transaction_id),
+        )
+    }
+
+    pub fn query_b_scan_c_payment(
+        &mut self,
+        transaction_date: &str,
+        partner_transaction_id: &str,
+    ) -> Result<Vec<BillPaymentTransaction>, SCBAPIError> {
+        self.runtime.block_on(
+            self.inner
+                .query_b_scan_c_payment(transaction_date, partner_transaction_id),
+        )
+    }
+}
+
+// Blocking counterpart of query_bill_payment_transaction_stream
+pub struct BillPaymentTransactionIter {
+    stream: BoxStream<'static, Result<BillPaymentTransaction, BillPaymentInquiryError>>,
+    runtime: Arc<Runtime>,
+}
+
+impl Iterator for BillPaymentTransactionIter {
+    type Item = Result<BillPaymentTransaction, BillPaymentInquiryError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
+
+impl fmt::Debug for BillPaymentTransactionIter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BillPaymentTransactionIter").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocking_client_clone_shares_runtime() {
+        let mut client = SCBClientAPI::new("app", "key", "secret");
+        client.set_ref3_prefix("ABC");
+        let clone = client.clone();
+        assert!(Arc::ptr_eq(&client.runtime, &clone.runtime));
+        assert_eq!(clone.ref3_generator().unwrap().prefix(), "ABC");
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use crate::errors::scb_error::SCBAPIError;
+use crate::frameworks::apis::pipeline::Endpoint;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CircuitBreakerConfig {
+    // Number of latest requests the failure rate is computed over
+    pub window_size: usize,
+    // Requests needed in the window before the circuit can open
+    pub minimum_requests: usize,
+    // Failure rate from 0.0 to 1.0 which opens the circuit
+    pub failure_rate_threshold: f64,
+    // Time the circuit stays open before letting probes through
+    pub open_duration: Duration,
+    // Successful probes needed in half-open state to close the circuit
+    pub half_open_probes: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        CircuitBreakerConfig {
+            window_size: 20,
+            minimum_requests: 10,
+            failure_rate_threshold: 0.5,
+            open_duration: Duration::from_secs(30),
+            half_open_probes: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    // Requests go through
+    Closed,
+    // Requests fail with SCBAPIError::CircuitOpen
+    Open,
+    // A limited number of probe requests go through to test recovery
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct Circuit {
+    config: CircuitBreakerConfig,
+    state: CircuitState,
+    // true for a failed request, latest last
+    outcomes: VecDeque<bool>,
+    // When the circuit opened or became half-open
+    changed_at: Instant,
+    probes_in_flight: u32,
+    probe_successes: u32,
+}
+
+impl Circuit {
+    fn new(config: CircuitBreakerConfig, now: Instant) -> Self {
+        Circuit {
+            config,
+            state: CircuitState::Closed,
+            outcomes: VecDeque::new(),
+            changed_at: now,
+            probes_in_flight: 0,
+            probe_successes: 0,
+        }
+    }
+
+    fn transition(&mut self, state: CircuitState, now: Instant) {
+        self.state = state;
+        self.changed_at = now;
+        self.probes_in_flight = 0;
+        self.probe_successes = 0;
+        if state == CircuitState::Closed {
+            self.outcomes.clear();
+        }
+    }
+}
+
+// Stops calling an endpoint of SCB while its transport failures are above a threshold,
+// so callers fail fast and can fall back instead of waiting on timeouts.
+// Each endpoint has its own circuit.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    default_config: CircuitBreakerConfig,
+    configs: HashMap<Endpoint, CircuitBreakerConfig>,
+    circuits: Mutex<HashMap<Endpoint, Circuit>>,
+}
+
+impl CircuitBreaker {
+    pub fn state(&self, endpoint: Endpoint) -> CircuitState {
+        let circuits = self.circuits.lock().unwrap();
+        circuits
+            .get(&endpoint)
+            .map(|c| c.state)
+            .unwrap_or(CircuitState::Closed)
+    }
+
+    // Let a request through or fail with SCBAPIError::CircuitOpen.
+    // Returns true for a half-open probe, whose outcome must be recorded to free its slot.
+    pub(crate) fn check(&self, endpoint: Endpoint) -> Result<bool, SCBAPIError> {
+        self.check_at(endpoint, Instant::now())
+    }
+
+    pub(crate) fn record(&self, endpoint: Endpoint, failed: bool) {
+        self.record_at(endpoint, failed, Instant::now())
+    }
+
+    // Free the slot of a probe which was not sent, e.g. rejected by the rate limiter,
+    // without counting it as a success or a failure
+    pub(crate) fn release_probe(&self, endpoint: Endpoint) {
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = self.circuit(&mut circuits, endpoint, Instant::now());
+        if circuit.state == CircuitState::HalfOpen {
+            circuit.probes_in_flight = circuit.probes_in_flight.saturating_sub(1);
+        }
+    }
+
+    fn check_at(&self, endpoint: Endpoint, now: Instant) -> Result<bool, SCBAPIError> {
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = self.circuit(&mut circuits, endpoint, now);
+        let open_duration = circuit.config.open_duration;
+        let elapsed = now.saturating_duration_since(circuit.changed_at);
+        match circuit.state {
+            CircuitState::Closed => Ok(false),
+            CircuitState::Open if elapsed >= open_duration => {
+                info!("Circuit of {} is half-open", endpoint);
+                circuit.transition(CircuitState::HalfOpen, now);
+                circuit.probes_in_flight = 1;
+                Ok(true)
+            }
+            CircuitState::Open => Err(SCBAPIError::CircuitOpen {
+                endpoint: endpoint.to_string(),
+                retry_after: open_duration - elapsed,
+            }),
+            CircuitState::HalfOpen => {
+                // Probes which never reported back, e.g. cancelled requests, are given up
+                // after another open duration
+                if elapsed >= open_duration {
+                    circuit.changed_at = now;
+                    circuit.probes_in_flight = 0;
+                }
+                if circuit.probes_in_flight + circuit.probe_successes
+                    < circuit.config.half_open_probes
+                {
+                    circuit.probes_in_flight += 1;
+                    Ok(true)
+                } else {
+                    Err(SCBAPIError::CircuitOpen {
+                        endpoint: endpoint.to_string(),
+                        retry_after: open_duration.saturating_sub(elapsed),
+                    })
+                }
+            }
+        }
+    }
+
+    fn record_at(&self, endpoint: Endpoint, failed: bool, now: Instant) {
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = self.circuit(&mut circuits, endpoint, now);
+        match circuit.state {
+            CircuitState::Closed => {
+                circuit.outcomes.push_back(failed);
+                while circuit.outcomes.len() > circuit.config.window_size {
+                    circuit.outcomes.pop_front();
+                }
+                let failures = circuit.outcomes.iter().filter(|f| **f).count();
+                let requests = circuit.outcomes.len();
+                if requests >= circuit.config.minimum_requests
+                    && failures as f64 >= circuit.config.failure_rate_threshold * requests as f64
+                {
+                    warn!(
+                        "Circuit of {} is open, {} of {} requests failed",
+                        endpoint, failures, requests
+                    );
+                    circuit.transition(CircuitState::Open, now);
+                }
+            }
+            CircuitState::HalfOpen if failed => {
+                warn!("Circuit of {} is open again, probe failed", endpoint);
+                circuit.transition(CircuitState::Open, now);
+            }
+            CircuitState::HalfOpen => {
+                circuit.probes_in_flight = circuit.probes_in_flight.saturating_sub(1);
+                circuit.probe_successes += 1;
+                if circuit.probe_successes >= circuit.config.half_open_probes {
+                    info!("Circuit of {} is closed", endpoint);
+                    circuit.transition(CircuitState::Closed, now);
+                }
+            }
+            // Requests started before the circuit opened
+            CircuitState::Open => {}
+        }
+    }
+
+    fn circuit<'a>(
+        &self,
+        circuits: &'a mut HashMap<Endpoint, Circuit>,
+        endpoint: Endpoint,
+        now: Instant,
+    ) -> &'a mut Circuit {
+        circuits.entry(endpoint).or_insert_with(|| {
+            let config = self
+                .configs
+                .get(&endpoint)
+                .copied()
+                .unwrap_or(self.default_config);
+            Circuit::new(config, now)
+        })
+    }
+}
+
+pub struct CircuitBreakerBuilder {
+    default_config: CircuitBreakerConfig,
+    configs: HashMap<Endpoint, CircuitBreakerConfig>,
+}
+
+impl CircuitBreakerBuilder {
+    // `default_config` applies to the endpoints without their own config
+    pub fn new(default_config: CircuitBreakerConfig) -> Self {
+        CircuitBreakerBuilder {
+            default_config,
+            configs: HashMap::new(),
+        }
+    }
+    pub fn add_endpoint_config(
+        &mut self,
+        endpoint: Endpoint,
+        config: CircuitBreakerConfig,
+    ) -> &mut Self {
+        self.configs.insert(endpoint, config);
+        self
+    }
+
+    pub fn build(&self) -> Result<CircuitBreaker, &'static str> {
+        for config in self.configs.values().chain([&self.default_config]) {
+            if config.window_size == 0 || config.minimum_requests > config.window_size {
+                return Err("minimum requests must be between 1 and the window size");
+            }
+            if !(config.failure_rate_threshold > 0.0 && config.failure_rate_threshold <= 1.0) {
+                return Err("failure rate threshold must be above 0.0 and at most 1.0");
+            }
+            if config.half_open_probes == 0 {
+                return Err("half-open probes must be at least 1");
+            }
+        }
+        Ok(CircuitBreaker {
+            default_config: self.default_config,
+            configs: self.configs.clone(),
+            circuits: Mutex::new(HashMap::new()),
+        })
+    }
+}
+
+impl Default for CircuitBreakerBuilder {
+    fn default() -> Self {
+        Self::new(CircuitBreakerConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreakerBuilder::new(CircuitBreakerConfig {
+            window_size: 4,
+            minimum_requests: 4,
+            failure_rate_threshold: 0.5,
+            open_duration: Duration::from_secs(10),
+            half_open_probes: 1,
+        })
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_circuit_opens_on_failure_rate() {
+        let breaker = breaker();
+        let now = Instant::now();
+        for failed in [false, true, false] {
+            breaker.check_at(Endpoint::Inquiry, now).unwrap();
+            breaker.record_at(Endpoint::Inquiry, failed, now);
+        }
+        assert_eq!(breaker.state(Endpoint::Inquiry), CircuitState::Closed);
+        breaker.record_at(Endpoint::Inquiry, true, now);
+        assert_eq!(breaker.state(Endpoint::Inquiry), CircuitState::Open);
+        assert!(matches!(
+            breaker.check_at(Endpoint::Inquiry, now + Duration::from_secs(1)),
+            Err(SCBAPIError::CircuitOpen { retry_after, .. }) if retry_after == Duration::from_secs(9)
+        ));
+        // Other endpoints have their own circuit
+        assert!(breaker.check_at(Endpoint::OAuth, now).is_ok());
+    }
+
+    #[test]
+    fn test_half_open_probe() {
+        let breaker = breaker();
+        let now = Instant::now();
+        for _ in 0..4 {
+            breaker.record_at(Endpoint::Inquiry, true, now);
+        }
+        let later = now + Duration::from_secs(10);
+        // One probe at a time
+        assert!(breaker.check_at(Endpoint::Inquiry, later).is_ok());
+        assert_eq!(breaker.state(Endpoint::Inquiry), CircuitState::HalfOpen);
+        assert!(breaker.check_at(Endpoint::Inquiry, later).is_err());
+        // Failed probe opens the circuit again
+        // A released probe frees its slot and keeps the circuit half-open
+        breaker.release_probe(Endpoint::Inquiry);
+        assert_eq!(breaker.state(Endpoint::Inquiry), CircuitState::HalfOpen);
+        assert!(breaker.check_at(Endpoint::Inquiry, later).is_ok());
+        breaker.record_at(Endpoint::Inquiry, true, later);
+        assert_eq!(breaker.state(Endpoint::Inquiry), CircuitState::Open);
+
+        let much_later = later + Duration::from_secs(10);
+        assert!(breaker.check_at(Endpoint::Inquiry, much_later).is_ok());
+        breaker.record_at(Endpoint::Inquiry, false, much_later);
+        assert_eq!(breaker.state(Endpoint::Inquiry), CircuitState::Closed);
+    }
+}
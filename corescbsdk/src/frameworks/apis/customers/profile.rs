@@ -0,0 +1,31 @@
+use reqwest::Client;
+use tracing::debug;
+
+use crate::entities::base::AccessToken;
+use crate::entities::customer::CustomerProfileResponse;
+use crate::errors::scb_error::SCBAPIError;
+use crate::frameworks::apis::api_utils::{
+    api_url, generate_header, map_result, CUSTOMER_PROFILE_V2_URL,
+};
+
+// Read the profile of a customer, `user_token` must be the token granted by the customer
+// through the authorization code flow, not the application token.
+pub async fn get_customer_profile(
+    application_key: &str,
+    client: &Client,
+    user_token: &AccessToken,
+) -> Result<CustomerProfileResponse, SCBAPIError> {
+    let req = client
+        .get(api_url(CUSTOMER_PROFILE_V2_URL))
+        .headers(generate_header(application_key, &Some(user_token.clone())))
+        .build()
+        .expect("Failed to build request");
+
+    debug!("Request : {}", req.url());
+
+    let req = client
+        .execute(req)
+        .await
+        .map_err(SCBAPIError::HttpRequestError);
+    map_result::<CustomerProfileResponse>(req).await
+}
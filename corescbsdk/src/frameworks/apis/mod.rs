@@ -1,3 +1,12 @@
 mod api_utils;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod circuit_breaker;
+mod customers;
+mod oauth;
 mod payments;
+pub mod pipeline;
+pub mod rate_limit;
 pub mod scb;
+#[cfg(feature = "metrics")]
+pub mod telemetry;
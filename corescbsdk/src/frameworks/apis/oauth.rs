@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use reqwest::header::HeaderValue;
+use reqwest::Client;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::entities::base::{AccessToken, SCBAccessTokenRequest, SCBRefreshTokenRequest};
+use crate::entities::oauth::{AuthorizeResponse, PkceChallenge};
+use crate::errors::scb_error::SCBAPIError;
+use crate::frameworks::apis::api_utils::{
+    api_url, generate_header, map_result, OAUTH_AUTHORIZE_V2_URL, OAUTH_TOKEN_REFRESH_V1_URL,
+    OAUTH_TOKEN_V1_URL,
+};
+use crate::frameworks::apis::pipeline::{Endpoint, RequestPipeline};
+#[cfg(feature = "metrics")]
+use crate::frameworks::apis::telemetry;
+use crate::frameworks::credentials::Credentials;
+
+pub async fn request_token(
+    resource_owner_id: &str,
+    client: &Client,
+    request: &SCBAccessTokenRequest<'_>,
+) -> Result<AccessToken, SCBAPIError> {
+    let req = client
+        .post(api_url(OAUTH_TOKEN_V1_URL))
+        .headers(generate_header(resource_owner_id, &None))
+        .json(request)
+        .send()
+        .await
+        .map_err(SCBAPIError::HttpRequestError);
+    map_result::<AccessToken>(req).await
+}
+
+// Application token shared by requests running outside the client, e.g. the inquiries of a
+// stream, requested again once it has expired
+#[derive(Debug, Clone)]
+pub(crate) struct ApplicationTokenSource {
+    credentials: Credentials,
+    client: Client,
+    pipeline: RequestPipeline,
+    token: Arc<Mutex<AccessToken>>,
+}
+
+impl ApplicationTokenSource {
+    pub(crate) fn new(
+        credentials: Credentials,
+        client: Client,
+        pipeline: RequestPipeline,
+        token: AccessToken,
+    ) -> Self {
+        ApplicationTokenSource {
+            credentials,
+            client,
+            pipeline,
+            token: Arc::new(Mutex::new(token)),
+        }
+    }
+
+    pub(crate) async fn access_token(&self) -> Result<AccessToken, SCBAPIError> {
+        // Held while requesting so concurrent requests wait for one new token
+        let mut token = self.token.lock().await;
+        if token.is_expired() {
+            debug!("Application access token expired, requesting a new one");
+            let request = SCBAccessTokenRequest {
+                application_key: self.credentials.application_key.to_string(),
+                application_secret: &self.credentials.secret_key,
+                auth_code: None,
+                state: None,
+                code_challenge: None,
+            };
+            *token = self
+                .pipeline
+                .run(
+                    Endpoint::OAuth,
+                    request_token(&self.credentials.application_name, &self.client, &request),
+                )
+                .await?;
+            #[cfg(feature = "metrics")]
+            telemetry::record_token_refresh("application");
+        }
+        Ok(token.clone())
+    }
+}
+
+pub async fn refresh_token(
+    resource_owner_id: &str,
+    client: &Client,
+    request: &SCBRefreshTokenRequest<'_>,
+) -> Result<AccessToken, SCBAPIError> {
+    let req = client
+        .post(api_url(OAUTH_TOKEN_REFRESH_V1_URL))
+        .headers(generate_header(resource_owner_id, &None))
+        .json(request)
+        .send()
+        .await
+        .map_err(SCBAPIError::HttpRequestError);
+    map_result::<AccessToken>(req).await
+}
+
+pub async fn authorize(
+    application_key: &str,
+    application_secret: &str,
+    client: &Client,
+    end_state: &str,
+    state: &str,
+    pkce: &PkceChallenge,
+) -> Result<AuthorizeResponse, SCBAPIError> {
+    let mut headers = generate_header(application_key, &None);
+    headers.insert("apikey", header_value(application_key)?);
+    headers.insert("apisecret", header_value(application_secret)?);
+    headers.insert("response-channel", HeaderValue::from_static("mobile"));
+    headers.insert("endState", header_value(end_state)?);
+
+    let req = client
+        .get(api_url(OAUTH_AUTHORIZE_V2_URL))
+        .query(&[
+            ("state", state),
+            ("code_challenge", pkce.code_challenge.as_str()),
+            ("code_challenge_method", pkce.code_challenge_method.as_str()),
+        ])
+        .headers(headers)
+        .build()
+        .expect("Failed to build request");
+
+    debug!("Request : {:#?}", req.url());
+
+    let req = client
+        .execute(req)
+        .await
+        .map_err(SCBAPIError::HttpRequestError);
+    map_result::<AuthorizeResponse>(req).await
+}
+
+fn header_value(value: &str) -> Result<HeaderValue, SCBAPIError> {
+    HeaderValue::from_str(value)
+        .map_err(|e| SCBAPIError::AuthorizationError(format!("invalid header value: {}", e)))
+}
@@ -0,0 +1,61 @@
+use reqwest::Client;
+use tracing::debug;
+
+use crate::entities::b_scan_c::{
+    BScanCPaymentRequest, BScanCPaymentResponse, BScanCVoidRequest, BScanCVoidResponse,
+};
+use crate::entities::base::AccessToken;
+use crate::errors::scb_error::SCBAPIError;
+use crate::frameworks::apis::api_utils::{
+    api_url, generate_header, map_result, B_SCAN_C_PAYMENT_V1_URL, B_SCAN_C_VOID_V1_URL,
+};
+
+pub async fn b_scan_c_payment(
+    application_key: &str,
+    client: &Client,
+    access_token: &AccessToken,
+    payment_request: &BScanCPaymentRequest,
+) -> Result<BScanCPaymentResponse, SCBAPIError> {
+    let req = client
+        .post(api_url(B_SCAN_C_PAYMENT_V1_URL))
+        .headers(generate_header(
+            application_key,
+            &Some(access_token.clone()),
+        ))
+        .json(payment_request)
+        .build()
+        .expect("Failed to build request");
+
+    debug!("Request : {:#?}", req);
+
+    let req = client
+        .execute(req)
+        .await
+        .map_err(SCBAPIError::HttpRequestError);
+    map_result::<BScanCPaymentResponse>(req).await
+}
+
+pub async fn b_scan_c_void(
+    application_key: &str,
+    client: &Client,
+    access_token: &AccessToken,
+    void_request: &BScanCVoidRequest,
+) -> Result<BScanCVoidResponse, SCBAPIError> {
+    let req = client
+        .post(api_url(B_SCAN_C_VOID_V1_URL))
+        .headers(generate_header(
+            application_key,
+            &Some(access_token.clone()),
+        ))
+        .json(void_request)
+        .build()
+        .expect("Failed to build request");
+
+    debug!("Request : {:#?}", req);
+
+    let req = client
+        .execute(req)
+        .await
+        .map_err(SCBAPIError::HttpRequestError);
+    map_result::<BScanCVoidResponse>(req).await
+}
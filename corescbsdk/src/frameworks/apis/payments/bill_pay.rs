@@ -1,30 +1,35 @@
-use log::debug;
+use futures::stream::{self, BoxStream, StreamExt};
 use reqwest::Client;
+use tracing::debug;
 
+use crate::entities::bank_code::BankCode;
 use crate::entities::base::AccessToken;
 use crate::entities::bill_pay::{
-    BillPaymentInquiryRequest, BillPaymentTransaction, BillPaymentTransactionSlip,
+    BillPaymentInquiryError, BillPaymentInquiryRequest, BillPaymentTransaction,
+    BillPaymentTransactionSlip,
 };
 use crate::errors::scb_error::SCBAPIError;
 use crate::frameworks::apis::api_utils::{
-    api_url, BILL_PAYMENT_TRANSACTION_V1_URL, generate_header, INQUIRY_BILL_PAYMENT_TRANSACTION_V1_URL,
-    map_result,
+    api_url, generate_header, map_result, BILL_PAYMENT_TRANSACTION_V1_URL,
+    INQUIRY_BILL_PAYMENT_TRANSACTION_V1_URL,
 };
+use crate::frameworks::apis::oauth::ApplicationTokenSource;
+use crate::frameworks::apis::pipeline::{Endpoint, RequestPipeline};
 
 pub async fn get_bill_payment_transaction(
-    application_key: &String,
+    application_key: &str,
     client: &Client,
     access_token: &AccessToken,
     trans_ref: &String,
-    sending_bank: &String,
+    sending_bank: &BankCode,
 ) -> Result<BillPaymentTransactionSlip, SCBAPIError> {
     let url = format!("{}/{}", BILL_PAYMENT_TRANSACTION_V1_URL, trans_ref);
 
     let req = client
         .get(api_url(&url))
-        .query(&[("sendingBank", sending_bank)])
+        .query(&[("sendingBank", sending_bank.code())])
         .headers(generate_header(
-            &application_key,
+            application_key,
             &Some(access_token.clone()),
         ))
         .build()
@@ -35,12 +40,12 @@ pub async fn get_bill_payment_transaction(
     let req = client
         .execute(req)
         .await
-        .map_err(|e| SCBAPIError::HttpRequestError(e));
+        .map_err(SCBAPIError::HttpRequestError);
     map_result::<BillPaymentTransactionSlip>(req).await
 }
 
 pub async fn query_bill_payment_transaction(
-    application_key: &String,
+    application_key: &str,
     client: &Client,
     access_token: &AccessToken,
     params: &BillPaymentInquiryRequest,
@@ -49,7 +54,7 @@ pub async fn query_bill_payment_transaction(
         .get(api_url(INQUIRY_BILL_PAYMENT_TRANSACTION_V1_URL))
         .query(params)
         .headers(generate_header(
-            &application_key,
+            application_key,
             &Some(access_token.clone()),
         ))
         .build()
@@ -60,6 +65,117 @@ pub async fn query_bill_payment_transaction(
     let req = client
         .execute(req)
         .await
-        .map_err(|e| SCBAPIError::HttpRequestError(e));
+        .map_err(SCBAPIError::HttpRequestError);
     map_result::<Vec<BillPaymentTransaction>>(req).await
 }
+
+// Run the inquiries with at most `concurrency` requests in flight. Transactions are yielded
+// in the order of `requests`, a failed inquiry is yielded as one error and the stream goes on.
+// The access token is checked before each inquiry, so a long range outlives it.
+pub(crate) fn query_bill_payment_transaction_stream(
+    pipeline: RequestPipeline,
+    application_key: String,
+    client: Client,
+    token_source: ApplicationTokenSource,
+    requests: Vec<BillPaymentInquiryRequest>,
+    concurrency: usize,
+) -> BoxStream<'static, Result<BillPaymentTransaction, BillPaymentInquiryError>> {
+    stream::iter(requests)
+        .map(move |params| {
+            let application_key = application_key.clone();
+            let client = client.clone();
+            let token_source = token_source.clone();
+            let pipeline = pipeline.clone();
+            async move {
+                let transactions = async {
+                    let access_token = token_source.access_token().await?;
+                    pipeline
+                        .run(
+                            Endpoint::Inquiry,
+                            query_bill_payment_transaction(
+                                &application_key,
+                                &client,
+                                &access_token,
+                                &params,
+                            ),
+                        )
+                        .await
+                };
+                match transactions.await {
+                    Ok(transactions) => transactions.into_iter().map(Ok).collect(),
+                    Err(error) => vec![Err(BillPaymentInquiryError {
+                        transaction_date: params.transaction_date,
+                        biller_id: params.biller_id,
+                        reference1: params.reference1,
+                        error,
+                    })],
+                }
+            }
+        })
+        .buffered(concurrency.max(1))
+        .flat_map(stream::iter)
+        .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::frameworks::apis::circuit_breaker::{CircuitBreakerBuilder, CircuitBreakerConfig};
+    use crate::frameworks::credentials::Credentials;
+
+    #[tokio::test]
+    async fn test_stream_requests_expired_token() {
+        let circuit_breaker = CircuitBreakerBuilder::new(CircuitBreakerConfig {
+            window_size: 1,
+            minimum_requests: 1,
+            failure_rate_threshold: 1.0,
+            open_duration: Duration::from_secs(60),
+            half_open_probes: 1,
+        })
+        .build()
+        .unwrap();
+        // The token request fails fast instead of calling SCB
+        circuit_breaker.record(Endpoint::OAuth, true);
+        let pipeline = RequestPipeline {
+            rate_limiter: None,
+            circuit_breaker: Some(Arc::new(circuit_breaker)),
+        };
+        let expired: AccessToken = serde_json::from_value(serde_json::json!({
+            "accessToken": "token",
+            "tokenType": "Bearer",
+            "expiresIn": 1800,
+            "expiresAt": 0
+        }))
+        .unwrap();
+        let token_source = ApplicationTokenSource::new(
+            Credentials::new("app", "key", "secret"),
+            Client::new(),
+            pipeline.clone(),
+            expired,
+        );
+        let requests = vec![
+            BillPaymentInquiryRequest::for_biller("2023-01-30", "B1", "ORDER1"),
+            BillPaymentInquiryRequest::for_biller("2023-01-31", "B1", "ORDER1"),
+        ];
+
+        let items: Vec<_> = query_bill_payment_transaction_stream(
+            pipeline,
+            "key".to_string(),
+            Client::new(),
+            token_source,
+            requests,
+            2,
+        )
+        .collect()
+        .await;
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|item| matches!(
+            item,
+            Err(BillPaymentInquiryError { error: SCBAPIError::CircuitOpen { endpoint, .. }, .. })
+                if endpoint == "oauth"
+        )));
+    }
+}
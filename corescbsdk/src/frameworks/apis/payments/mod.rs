@@ -1,2 +1,3 @@
+pub(crate) mod b_scan_c;
 pub(crate) mod bill_pay;
 pub mod qr_code;
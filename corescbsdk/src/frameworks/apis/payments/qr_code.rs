@@ -1,24 +1,29 @@
-use log::debug;
 use reqwest::Client;
+use tracing::debug;
 
 use crate::entities::base::AccessToken;
-use crate::entities::qr_code::{QRCodeRequest, QRCodeResponse};
+use crate::entities::qr_code::{
+    QRCodeCancelResponse, QRCodeRequest, QRCodeResponse, QRCreditCardTransaction,
+};
 use crate::errors::scb_error::SCBAPIError;
 use crate::frameworks::apis::api_utils::{
-    api_url, generate_header, map_result, QRCODE_CREATE_V1_URL,
+    api_url, generate_header, generate_header_with_request_uid, map_result, QRCODE_CREATE_V1_URL,
+    QRCODE_CREDIT_CARD_V1_URL,
 };
 
 pub async fn qr_code_create(
-    application_key: &String,
+    application_key: &str,
     client: &Client,
     access_token: &AccessToken,
     qrcode_request: &QRCodeRequest,
+    request_uid: &str,
 ) -> Result<QRCodeResponse, SCBAPIError> {
     let req = client
         .post(api_url(QRCODE_CREATE_V1_URL))
-        .headers(generate_header(
-            &application_key,
+        .headers(generate_header_with_request_uid(
+            application_key,
             &Some(access_token.clone()),
+            request_uid,
         ))
         .json(qrcode_request)
         .build()
@@ -33,6 +38,58 @@ pub async fn qr_code_create(
     let req = client
         .execute(req)
         .await
-        .map_err(|e| SCBAPIError::HttpRequestError(e));
+        .map_err(SCBAPIError::HttpRequestError);
     map_result::<QRCodeResponse>(req).await
 }
+
+pub async fn qr_cs_transaction_inquiry(
+    application_key: &str,
+    client: &Client,
+    access_token: &AccessToken,
+    qr_code_id: &str,
+) -> Result<QRCreditCardTransaction, SCBAPIError> {
+    let url = format!("{}/{}", QRCODE_CREDIT_CARD_V1_URL, qr_code_id);
+
+    let req = client
+        .get(api_url(&url))
+        .headers(generate_header(
+            application_key,
+            &Some(access_token.clone()),
+        ))
+        .build()
+        .expect("Failed to build request");
+
+    debug!("Request : {:#?}", req);
+
+    let req = client
+        .execute(req)
+        .await
+        .map_err(SCBAPIError::HttpRequestError);
+    map_result::<QRCreditCardTransaction>(req).await
+}
+
+pub async fn qr_code_cancel(
+    application_key: &str,
+    client: &Client,
+    access_token: &AccessToken,
+    qr_code_id: &str,
+) -> Result<QRCodeCancelResponse, SCBAPIError> {
+    let url = format!("{}/{}", QRCODE_CREDIT_CARD_V1_URL, qr_code_id);
+
+    let req = client
+        .delete(api_url(&url))
+        .headers(generate_header(
+            application_key,
+            &Some(access_token.clone()),
+        ))
+        .build()
+        .expect("Failed to build request");
+
+    debug!("Request : {:#?}", req);
+
+    let req = client
+        .execute(req)
+        .await
+        .map_err(SCBAPIError::HttpRequestError);
+    map_result::<QRCodeCancelResponse>(req).await
+}
@@ -0,0 +1,251 @@
+#[cfg(feature = "metrics")]
+use std::cell::Cell;
+use std::fmt;
+use std::fmt::Formatter;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+use tracing::field::Empty;
+use tracing::{debug, info_span, Instrument};
+
+use crate::errors::scb_error::SCBAPIError;
+use crate::frameworks::apis::circuit_breaker::CircuitBreaker;
+use crate::frameworks::apis::rate_limit::RateLimiter;
+#[cfg(feature = "metrics")]
+use crate::frameworks::apis::telemetry;
+
+// Group of SCB APIs sharing limits, used to configure the request pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Endpoint {
+    // Access token request and refresh, customer consent
+    OAuth,
+    QRCodeCreate,
+    // QR CS transaction inquiry and cancel
+    QRCode,
+    SlipVerification,
+    // Bill payment inquiry
+    Inquiry,
+    BScanC,
+    Customer,
+}
+
+impl Endpoint {
+    pub const ALL: &'static [Endpoint] = &[
+        Endpoint::OAuth,
+        Endpoint::QRCodeCreate,
+        Endpoint::QRCode,
+        Endpoint::SlipVerification,
+        Endpoint::Inquiry,
+        Endpoint::BScanC,
+        Endpoint::Customer,
+    ];
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Endpoint::OAuth => write!(f, "oauth"),
+            Endpoint::QRCodeCreate => write!(f, "qr_code_create"),
+            Endpoint::QRCode => write!(f, "qr_code"),
+            Endpoint::SlipVerification => write!(f, "slip_verification"),
+            Endpoint::Inquiry => write!(f, "inquiry"),
+            Endpoint::BScanC => write!(f, "b_scan_c"),
+            Endpoint::Customer => write!(f, "customer"),
+        }
+    }
+}
+
+// Steps every SCB request goes through. Shared by clones of SCBClientAPI.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RequestPipeline {
+    pub(crate) rate_limiter: Option<Arc<RateLimiter>>,
+    pub(crate) circuit_breaker: Option<Arc<CircuitBreaker>>,
+}
+
+impl RequestPipeline {
+    pub(crate) async fn run<T, F>(&self, endpoint: Endpoint, request: F) -> Result<T, SCBAPIError>
+    where
+        F: Future<Output = Result<T, SCBAPIError>>,
+    {
+        // request_uid, http_status and scb_status are recorded by api_utils while the request runs
+        let span = info_span!(
+            "scb_request",
+            endpoint = %endpoint,
+            request_uid = Empty,
+            http_status = Empty,
+            scb_status = Empty,
+            latency_ms = Empty,
+            rate_limit_wait_ms = Empty,
+            rejected = Empty,
+        );
+        let admitted = self.admit(endpoint).instrument(span.clone()).await;
+        if let Err(e) = &admitted {
+            span.record("rejected", tracing::field::display(e));
+            span.in_scope(|| debug!("Request rejected: {}", e));
+            #[cfg(feature = "metrics")]
+            telemetry::record_request(endpoint, Some(e), None, None);
+        }
+        let wait = admitted?;
+        span.record("rate_limit_wait_ms", wait.as_millis() as u64);
+        let started_at = Instant::now();
+        #[cfg(feature = "metrics")]
+        let (result, scb_status) = telemetry::SCB_STATUS
+            .scope(Cell::new(None), async {
+                let result = request.instrument(span.clone()).await;
+                (result, telemetry::SCB_STATUS.with(Cell::get))
+            })
+            .await;
+        #[cfg(not(feature = "metrics"))]
+        let result = request.instrument(span.clone()).await;
+        let latency = started_at.elapsed();
+
+        span.record("latency_ms", latency.as_millis() as u64);
+        if let Err(e) = &result {
+            span.in_scope(|| debug!("Request failed: {}", e));
+        }
+        if let Some(circuit_breaker) = &self.circuit_breaker {
+            // Transport failures, timeouts, 5xx and undecodable bodies count,
+            // errors returned by SCB mean the gateway is up
+            let failed = matches!(&result, Err(e) if e.is_gateway_failure());
+            circuit_breaker.record(endpoint, failed);
+        }
+        #[cfg(feature = "metrics")]
+        telemetry::record_request(endpoint, result.as_ref().err(), Some(latency), scb_status);
+        result
+    }
+
+    // Circuit breaker and rate limiter, before the request is sent. Returns the rate limit wait.
+    async fn admit(&self, endpoint: Endpoint) -> Result<Duration, SCBAPIError> {
+        let probe = match &self.circuit_breaker {
+            Some(circuit_breaker) => circuit_breaker.check(endpoint)?,
+            None => false,
+        };
+        let Some(rate_limiter) = &self.rate_limiter else {
+            return Ok(Duration::ZERO);
+        };
+        let acquired = rate_limiter.acquire(endpoint).await;
+        if let (Err(_), Some(circuit_breaker)) = (&acquired, &self.circuit_breaker) {
+            // SCB was not called, so the probe says nothing about its health
+            if probe {
+                circuit_breaker.release_probe(endpoint);
+            }
+        }
+        let wait = acquired?;
+        #[cfg(feature = "metrics")]
+        telemetry::record_rate_limit_wait(endpoint, wait);
+        Ok(wait)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::Subscriber;
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::registry::LookupSpan;
+    use tracing_subscriber::Layer;
+
+    use super::*;
+    use crate::frameworks::apis::circuit_breaker::{
+        CircuitBreakerBuilder, CircuitBreakerConfig, CircuitState,
+    };
+    use crate::frameworks::apis::rate_limit::{RateLimit, RateLimiterBuilder};
+
+    // "name<parent" of new spans and "name.field" of recorded fields
+    #[derive(Clone, Default)]
+    struct SpanEvents(Arc<Mutex<Vec<String>>>);
+
+    impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for SpanEvents {
+        fn on_new_span(&self, _: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+            let span = ctx.span(id).unwrap();
+            let parent = span.parent().map(|p| p.name()).unwrap_or_default();
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{}<{}", span.name(), parent));
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+            let name = ctx.span(id).unwrap().name();
+            let mut events = self.0.lock().unwrap();
+            values.record(&mut |field: &tracing::field::Field, _: &dyn fmt::Debug| {
+                events.push(format!("{}.{}", name, field.name()))
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejected_request_span() {
+        let events = SpanEvents::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(events.clone()));
+        let circuit_breaker = CircuitBreakerBuilder::new(CircuitBreakerConfig {
+            window_size: 1,
+            minimum_requests: 1,
+            failure_rate_threshold: 1.0,
+            open_duration: Duration::from_secs(60),
+            half_open_probes: 1,
+        })
+        .build()
+        .unwrap();
+        circuit_breaker.record(Endpoint::Inquiry, true);
+        let pipeline = RequestPipeline {
+            rate_limiter: None,
+            circuit_breaker: Some(Arc::new(circuit_breaker)),
+        };
+
+        let result: Result<(), _> = pipeline
+            .run(Endpoint::Inquiry, async { Ok(()) })
+            .instrument(info_span!("checkout"))
+            .await;
+        assert!(matches!(result, Err(SCBAPIError::CircuitOpen { .. })));
+        let events = events.0.lock().unwrap();
+        assert!(events.contains(&"scb_request<checkout".to_string()));
+        assert!(events.contains(&"scb_request.rejected".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_probe_rejected_by_rate_limiter() {
+        let circuit_breaker = CircuitBreakerBuilder::new(CircuitBreakerConfig {
+            window_size: 1,
+            minimum_requests: 1,
+            failure_rate_threshold: 1.0,
+            open_duration: Duration::ZERO,
+            half_open_probes: 1,
+        })
+        .build()
+        .unwrap();
+        let rate_limiter = RateLimiterBuilder::new()
+            .add_limit(Endpoint::Inquiry, RateLimit::fail_fast(1, 0.001))
+            .build()
+            .unwrap();
+        let pipeline = RequestPipeline {
+            rate_limiter: Some(Arc::new(rate_limiter)),
+            circuit_breaker: Some(Arc::new(circuit_breaker)),
+        };
+
+        // An undecodable body counts as a failure
+        let result: Result<(), _> = pipeline
+            .run(Endpoint::Inquiry, async {
+                Err(SCBAPIError::InvalidResponse("<html>".to_string()))
+            })
+            .await;
+        assert!(result.is_err());
+        let circuit_breaker = pipeline.circuit_breaker.as_ref().unwrap();
+        assert_eq!(circuit_breaker.state(Endpoint::Inquiry), CircuitState::Open);
+
+        // The probe is rate limited, its slot is given back and the circuit stays half-open
+        let result: Result<(), _> = pipeline.run(Endpoint::Inquiry, async { Ok(()) }).await;
+        assert!(matches!(result, Err(SCBAPIError::RateLimited { .. })));
+        assert_eq!(
+            circuit_breaker.state(Endpoint::Inquiry),
+            CircuitState::HalfOpen
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::time::Instant;
+use tracing::debug;
+
+use crate::errors::scb_error::SCBAPIError;
+use crate::frameworks::apis::pipeline::Endpoint;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitMode {
+    // Wait for a token, failing when the wait would be longer than max_wait
+    Queue { max_wait: Option<Duration> },
+    // Fail with SCBAPIError::RateLimited when no token is available
+    FailFast,
+}
+
+// Token bucket of one endpoint: bursts of up to `capacity` requests,
+// refilled at `per_second` requests per second
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    pub capacity: u32,
+    pub per_second: f64,
+    pub mode: RateLimitMode,
+}
+
+impl RateLimit {
+    pub fn queue(capacity: u32, per_second: f64) -> Self {
+        RateLimit {
+            capacity,
+            per_second,
+            mode: RateLimitMode::Queue { max_wait: None },
+        }
+    }
+
+    pub fn fail_fast(capacity: u32, per_second: f64) -> Self {
+        RateLimit {
+            capacity,
+            per_second,
+            mode: RateLimitMode::FailFast,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimitStats {
+    // Requests let through, with or without waiting
+    pub acquired: u64,
+    // Requests which had to wait for a token
+    pub queued: u64,
+    pub rejected: u64,
+    pub total_wait: Duration,
+    pub max_wait: Duration,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    // Negative when requests are queued for future tokens
+    tokens: f64,
+    updated_at: Instant,
+    stats: RateLimitStats,
+}
+
+// Client side token bucket limiter per endpoint. Endpoints without a limit are not throttled.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<Endpoint, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn stats(&self, endpoint: Endpoint) -> Option<RateLimitStats> {
+        let buckets = self.buckets.lock().unwrap();
+        buckets.get(&endpoint).map(|b| b.stats)
+    }
+
+    // Take a token for one request, waiting in queue mode. Returns the time waited.
+    pub async fn acquire(&self, endpoint: Endpoint) -> Result<Duration, SCBAPIError> {
+        let wait = self.reserve(endpoint, Instant::now())?;
+        if !wait.is_zero() {
+            debug!("Rate limit of {} waits {:?}", endpoint, wait);
+            tokio::time::sleep(wait).await;
+        }
+        Ok(wait)
+    }
+
+    // Reserve a token at `now` and return how long to wait for it
+    fn reserve(&self, endpoint: Endpoint, now: Instant) -> Result<Duration, SCBAPIError> {
+        let mut buckets = self.buckets.lock().unwrap();
+        let Some(bucket) = buckets.get_mut(&endpoint) else {
+            return Ok(Duration::ZERO);
+        };
+        let limit = bucket.limit;
+        let elapsed = now.saturating_duration_since(bucket.updated_at);
+        bucket.tokens =
+            (bucket.tokens + elapsed.as_secs_f64() * limit.per_second).min(limit.capacity as f64);
+        bucket.updated_at = now;
+
+        let wait = if bucket.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - bucket.tokens) / limit.per_second)
+        };
+        let rejected = match limit.mode {
+            RateLimitMode::FailFast => !wait.is_zero(),
+            RateLimitMode::Queue { max_wait } => max_wait.is_some_and(|max| wait > max),
+        };
+        if rejected {
+            bucket.stats.rejected += 1;
+            return Err(SCBAPIError::RateLimited {
+                endpoint: endpoint.to_string(),
+                retry_after: wait,
+            });
+        }
+
+        bucket.tokens -= 1.0;
+        bucket.stats.acquired += 1;
+        if !wait.is_zero() {
+            bucket.stats.queued += 1;
+            bucket.stats.total_wait += wait;
+            bucket.stats.max_wait = bucket.stats.max_wait.max(wait);
+        }
+        Ok(wait)
+    }
+}
+
+pub struct RateLimiterBuilder {
+    limits: HashMap<Endpoint, RateLimit>,
+}
+
+impl RateLimiterBuilder {
+    pub fn new() -> Self {
+        RateLimiterBuilder {
+            limits: HashMap::new(),
+        }
+    }
+    pub fn add_limit(&mut self, endpoint: Endpoint, limit: RateLimit) -> &mut Self {
+        self.limits.insert(endpoint, limit);
+        self
+    }
+
+    pub fn build(&self) -> Result<RateLimiter, &'static str> {
+        let now = Instant::now();
+        let mut buckets = HashMap::new();
+        for (endpoint, limit) in &self.limits {
+            if limit.capacity == 0 {
+                return Err("rate limit capacity must be at least 1");
+            }
+            if !(limit.per_second > 0.0 && limit.per_second.is_finite()) {
+                return Err("rate limit per second must be positive");
+            }
+            buckets.insert(
+                *endpoint,
+                Bucket {
+                    limit: *limit,
+                    tokens: limit.capacity as f64,
+                    updated_at: now,
+                    stats: RateLimitStats::default(),
+                },
+            );
+        }
+        Ok(RateLimiter {
+            buckets: Mutex::new(buckets),
+        })
+    }
+}
+
+impl Default for RateLimiterBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fail_fast() {
+        let limiter = RateLimiterBuilder::new()
+            .add_limit(Endpoint::QRCodeCreate, RateLimit::fail_fast(2, 10.0))
+            .build()
+            .unwrap();
+        let now = Instant::now();
+        assert!(limiter.reserve(Endpoint::QRCodeCreate, now).is_ok());
+        assert!(limiter.reserve(Endpoint::QRCodeCreate, now).is_ok());
+        assert!(matches!(
+            limiter.reserve(Endpoint::QRCodeCreate, now),
+            Err(SCBAPIError::RateLimited { .. })
+        ));
+        // Refilled one token after 100ms
+        let later = now + Duration::from_millis(100);
+        assert!(limiter.reserve(Endpoint::QRCodeCreate, later).is_ok());
+        // Other endpoints are not limited
+        assert!(limiter.reserve(Endpoint::Inquiry, now).is_ok());
+
+        let stats = limiter.stats(Endpoint::QRCodeCreate).unwrap();
+        assert_eq!((stats.acquired, stats.rejected), (3, 1));
+    }
+
+    #[test]
+    fn test_queue() {
+        let limiter = RateLimiterBuilder::new()
+            .add_limit(Endpoint::OAuth, RateLimit::queue(1, 10.0))
+            .build()
+            .unwrap();
+        let now = Instant::now();
+        assert_eq!(
+            limiter.reserve(Endpoint::OAuth, now).unwrap(),
+            Duration::ZERO
+        );
+        // Queued requests wait one refill interval after each other
+        let first = limiter.reserve(Endpoint::OAuth, now).unwrap();
+        let second = limiter.reserve(Endpoint::OAuth, now).unwrap();
+        assert!((first.as_secs_f64() - 0.1).abs() < 1e-6);
+        assert!((second.as_secs_f64() - 0.2).abs() < 1e-6);
+        let stats = limiter.stats(Endpoint::OAuth).unwrap();
+        assert_eq!(stats.queued, 2);
+        assert_eq!(stats.max_wait, second);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits() {
+        let limiter = RateLimiterBuilder::new()
+            .add_limit(Endpoint::Inquiry, RateLimit::queue(1, 50.0))
+            .build()
+            .unwrap();
+        let start = Instant::now();
+        limiter.acquire(Endpoint::Inquiry).await.unwrap();
+        let waited = limiter.acquire(Endpoint::Inquiry).await.unwrap();
+        assert!(waited > Duration::ZERO);
+        assert!(start.elapsed() >= waited);
+    }
+}
@@ -1,64 +1,220 @@
-use log::{debug, error, info};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::stream::BoxStream;
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, instrument};
+use uuid::Uuid;
 
-use crate::entities::base::{AccessToken, SCBAccessTokenRequest};
+use crate::entities::b_scan_c::{
+    BScanCPaymentRequest, BScanCPaymentResponse, BScanCVoidRequest, BScanCVoidResponse,
+};
+use crate::entities::bank_code::BankCode;
+use crate::entities::base::{AccessToken, SCBAccessTokenRequest, SCBRefreshTokenRequest};
 use crate::entities::bill_pay::{
-    BillPaymentInquiryRequest, BillPaymentTransaction, BillPaymentTransactionSlip,
+    BillPaymentInquiryError, BillPaymentInquiryRequest, BillPaymentTransaction,
+    BillPaymentTransactionSlip,
 };
-use crate::entities::qr_code::{QRCodeRequest, QRCodeResponse};
+use crate::entities::customer::CustomerProfile;
+use crate::entities::oauth::{generate_state, AuthorizationSession, PkceChallenge};
+use crate::entities::payment_session::{PaymentOutcome, PaymentSession, PaymentSessionState};
+use crate::entities::qr_code::{
+    QRCodeCancelResponse, QRCodeRequest, QRCodeResponse, QRCreditCardStatus,
+    QRCreditCardTransaction,
+};
+use crate::entities::reconciliation::{reconcile, ExpectedPayment, ReconciliationReport};
+use crate::entities::reference::{ReferenceGenerator, ReferenceGeneratorBuilder};
+use crate::entities::slip_qr::SlipQRCode;
 use crate::errors::scb_error::SCBAPIError;
-use crate::frameworks::apis::api_utils::{
-    api_url, generate_header, map_result, OAUTH_TOKEN_V1_URL,
+use crate::frameworks::apis::circuit_breaker::CircuitBreaker;
+use crate::frameworks::apis::customers::profile;
+use crate::frameworks::apis::oauth::{self, ApplicationTokenSource};
+use crate::frameworks::apis::payments::qr_code::{
+    qr_code_cancel, qr_code_create, qr_cs_transaction_inquiry,
+};
+use crate::frameworks::apis::payments::{b_scan_c, bill_pay};
+use crate::frameworks::apis::pipeline::{Endpoint, RequestPipeline};
+use crate::frameworks::apis::rate_limit::RateLimiter;
+#[cfg(feature = "metrics")]
+use crate::frameworks::apis::telemetry;
+use crate::frameworks::config::{SCBConfig, SCBConfigError};
+use crate::frameworks::credentials::{CredentialProvider, Credentials};
+use crate::frameworks::stores::payment_store::{
+    PaymentQuery, PaymentRecord, PaymentStatus, PaymentStore,
 };
-use crate::frameworks::apis::payments::bill_pay;
-use crate::frameworks::apis::payments::qr_code::qr_code_create;
+use crate::frameworks::stores::slip_registry::SlipRegistry;
 
-#[derive(Debug, Serialize, Deserialize)]
+// Clones share the rate limiter, circuit breaker and stores, each clone keeps its own access token
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SCBClientAPI {
-    application_name: String,
-    application_key: String,
-    secret_key: String,
+    // The secret key is not serialized, see Credentials
+    #[serde(flatten)]
+    credentials: Credentials,
+    // When set, asked for the credentials before each token acquisition
+    #[serde(skip)]
+    credential_provider: Option<Arc<dyn CredentialProvider>>,
     access_token: Option<AccessToken>,
+    // User-scoped tokens obtained through customer consent, keyed by the partner's customer id
+    customer_tokens: HashMap<String, AccessToken>,
+    // Reference 3 Prefix of the merchant profile, used by ref3_generator
+    ref3_prefix: Option<String>,
+    // Default biller id of the merchant profile
+    biller_id: Option<String>,
+    // When set, slips verified for an order are claimed so they cannot be reused
+    #[serde(skip)]
+    slip_registry: Option<Arc<dyn SlipRegistry>>,
+    // When set, every QR created and its final status are recorded
+    #[serde(skip)]
+    payment_store: Option<Arc<dyn PaymentStore>>,
+    #[serde(skip)]
+    pipeline: RequestPipeline,
+    // Shared by the clones of this client. Not shared between clients because pooled
+    // connections are driven by the runtime which opened them, and each blocking client
+    // has its own runtime.
+    #[serde(skip, default = "create_client")]
+    http_client: reqwest::Client,
 }
 
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+// Whole request including the response body, a hung connection fails with HttpRequestError
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const CREDENTIAL_PROVIDER_TIMEOUT: Duration = Duration::from_secs(15);
+
 fn create_client() -> reqwest::Client {
-    reqwest::Client::new()
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .expect("failed to create the HTTP client")
+}
+
+fn credential_error(error: SCBConfigError) -> SCBAPIError {
+    SCBAPIError::AuthorizationError(format!("credential provider: {}", error))
 }
 
 impl SCBClientAPI {
-    pub fn new(
-        application_name: &String,
-        application_key: &String,
-        secret_key: &String,
-    ) -> SCBClientAPI {
+    pub fn new(application_name: &str, application_key: &str, secret_key: &str) -> SCBClientAPI {
         SCBClientAPI {
-            application_name: application_name.to_string(),
-            application_key: application_key.to_string(),
-            secret_key: secret_key.to_string(),
+            credentials: Credentials::new(application_name, application_key, secret_key),
+            credential_provider: None,
             access_token: None,
+            customer_tokens: HashMap::new(),
+            ref3_prefix: None,
+            biller_id: None,
+            slip_registry: None,
+            payment_store: None,
+            pipeline: RequestPipeline::default(),
+            http_client: create_client(),
+        }
+    }
+
+    // Client with the credentials and merchant defaults of the config
+    pub fn from_config(config: &SCBConfig) -> SCBClientAPI {
+        let mut client = SCBClientAPI::new(
+            &config.application_name,
+            &config.application_key,
+            &config.secret_key,
+        );
+        client.ref3_prefix.clone_from(&config.ref3_prefix);
+        client.biller_id.clone_from(&config.biller_id);
+        client
+    }
+
+    // Client whose credentials come from the provider, see set_credential_provider
+    pub fn from_credential_provider(
+        credential_provider: Arc<dyn CredentialProvider>,
+    ) -> Result<SCBClientAPI, SCBAPIError> {
+        let credentials = credential_provider
+            .credentials()
+            .map_err(credential_error)?;
+        let mut client = SCBClientAPI::new(
+            &credentials.application_name,
+            &credentials.application_key,
+            &credentials.secret_key,
+        );
+        client.credential_provider = Some(credential_provider);
+        Ok(client)
+    }
+
+    // Ask the provider for the credentials before each token request, including the consent
+    // flow and customer token refreshes. When the secret changes the application token is
+    // requested again, when the application changes the customer tokens are dropped as well.
+    pub fn set_credential_provider(&mut self, credential_provider: Arc<dyn CredentialProvider>) {
+        self.credential_provider = Some(credential_provider);
+    }
+
+    async fn refresh_credentials(&mut self) -> Result<(), SCBAPIError> {
+        let Some(credential_provider) = self.credential_provider.clone() else {
+            return Ok(());
+        };
+        // Providers may read files or run commands
+        let credentials = tokio::time::timeout(
+            CREDENTIAL_PROVIDER_TIMEOUT,
+            tokio::task::spawn_blocking(move || credential_provider.credentials()),
+        )
+        .await
+        .map_err(|_| {
+            SCBAPIError::AuthorizationError(format!(
+                "credential provider: no credentials after {:?}",
+                CREDENTIAL_PROVIDER_TIMEOUT
+            ))
+        })?
+        .map_err(|e| SCBAPIError::AuthorizationError(format!("credential provider: {}", e)))?
+        .map_err(credential_error)?;
+        if credentials == self.credentials {
+            return Ok(());
         }
+        if credentials.application_name != self.credentials.application_name
+            || credentials.application_key != self.credentials.application_key
+        {
+            info!("Application changed, dropping the application and customer access tokens");
+            self.customer_tokens.clear();
+        } else {
+            info!("Credentials changed, dropping the application access token");
+        }
+        self.credentials = credentials;
+        self.access_token = None;
+        Ok(())
+    }
+
+    // Throttle requests per endpoint, shared with the clones of this client
+    pub fn set_rate_limiter(&mut self, rate_limiter: Arc<RateLimiter>) {
+        self.pipeline.rate_limiter = Some(rate_limiter);
+    }
+
+    // Fail fast with SCBAPIError::CircuitOpen while an endpoint keeps failing
+    pub fn set_circuit_breaker(&mut self, circuit_breaker: Arc<CircuitBreaker>) {
+        self.pipeline.circuit_breaker = Some(circuit_breaker);
     }
 
     async fn request_access_token(&mut self) -> Result<(), SCBAPIError> {
+        self.refresh_credentials().await?;
         let request = SCBAccessTokenRequest {
-            application_key: self.application_key.to_string(),
-            application_secret: self.secret_key.to_string(),
+            application_key: self.credentials.application_key.to_string(),
+            application_secret: &self.credentials.secret_key,
             auth_code: None,
             state: None,
             code_challenge: None,
         };
 
-        let req = create_client()
-            .post(api_url(OAUTH_TOKEN_V1_URL))
-            .headers(generate_header(&self.application_name, &None))
-            .body(serde_json::to_string(&request).unwrap())
-            .send()
-            .await
-            .map_err(|e| SCBAPIError::HttpRequestError(e));
-
-        let res = map_result::<AccessToken>(req).await;
+        let res = self
+            .pipeline
+            .run(
+                Endpoint::OAuth,
+                oauth::request_token(
+                    &self.credentials.application_name,
+                    &self.http_client,
+                    &request,
+                ),
+            )
+            .await;
         match res {
             Ok(token) => {
+                #[cfg(feature = "metrics")]
+                telemetry::record_token_refresh("application");
                 self.access_token = Some(token);
                 Ok(())
             }
@@ -66,52 +222,614 @@ impl SCBClientAPI {
         }
     }
 
+    // Application access token, requested again when it has expired
+    #[instrument(skip_all)]
+    pub async fn get_access_token(&mut self) -> Result<AccessToken, SCBAPIError> {
+        self.get_access_token_if_need().await?;
+        Ok(self.access_token.clone().unwrap())
+    }
+
+    // Start the customer consent flow (/v2/oauth/authorize).
+    // The returned session must be kept until SCB redirects back with the auth code and state.
+    #[instrument(skip_all)]
+    pub async fn oauth_authorize(
+        &mut self,
+        end_state: &str,
+    ) -> Result<AuthorizationSession, SCBAPIError> {
+        self.refresh_credentials().await?;
+        let state = generate_state();
+        let pkce = PkceChallenge::new();
+        let res = self
+            .pipeline
+            .run(
+                Endpoint::OAuth,
+                oauth::authorize(
+                    &self.credentials.application_key,
+                    &self.credentials.secret_key,
+                    &self.http_client,
+                    end_state,
+                    &state,
+                    &pkce,
+                ),
+            )
+            .await?;
+
+        Ok(AuthorizationSession {
+            state,
+            pkce,
+            authorize_url: res.callback_url,
+        })
+    }
+
+    // Verify the state returned on the redirect URL and exchange the auth code for a
+    // user-scoped access token, which is kept for the given customer.
+    #[instrument(skip_all, fields(customer_id = %customer_id))]
+    pub async fn oauth_exchange_auth_code(
+        &mut self,
+        customer_id: &String,
+        session: &AuthorizationSession,
+        auth_code: &String,
+        state: &String,
+    ) -> Result<AccessToken, SCBAPIError> {
+        session.verify_state(state)?;
+        self.refresh_credentials().await?;
+
+        let request = SCBAccessTokenRequest {
+            application_key: self.credentials.application_key.to_string(),
+            application_secret: &self.credentials.secret_key,
+            auth_code: Some(auth_code.to_string()),
+            state: Some(state.to_string()),
+            // SCB expects the PKCE code verifier in this field on token exchange
+            code_challenge: Some(session.pkce.code_verifier.to_string()),
+        };
+        let token = self
+            .pipeline
+            .run(
+                Endpoint::OAuth,
+                oauth::request_token(
+                    &self.credentials.application_name,
+                    &self.http_client,
+                    &request,
+                ),
+            )
+            .await?;
+        self.customer_tokens
+            .insert(customer_id.to_string(), token.clone());
+        Ok(token)
+    }
+
+    // Return the user-scoped token for a customer, refreshing it when it has expired.
+    #[instrument(skip_all, fields(customer_id = %customer_id))]
+    pub async fn get_customer_access_token(
+        &mut self,
+        customer_id: &String,
+    ) -> Result<AccessToken, SCBAPIError> {
+        let token = self
+            .customer_tokens
+            .get(customer_id)
+            .cloned()
+            .ok_or_else(|| {
+                SCBAPIError::AuthorizationError(format!(
+                    "customer {} has not granted consent",
+                    customer_id
+                ))
+            })?;
+        if !token.is_expired() {
+            return Ok(token);
+        }
+        if !token.can_refresh() {
+            self.customer_tokens.remove(customer_id);
+            return Err(SCBAPIError::AuthorizationError(format!(
+                "consent of customer {} has expired",
+                customer_id
+            )));
+        }
+
+        debug!("Refresh access token of customer {}", customer_id);
+        self.refresh_credentials().await?;
+        let request = SCBRefreshTokenRequest {
+            application_key: self.credentials.application_key.to_string(),
+            application_secret: &self.credentials.secret_key,
+            refresh_token: token.refresh_token.clone().unwrap_or_default(),
+        };
+        let token = self
+            .pipeline
+            .run(
+                Endpoint::OAuth,
+                oauth::refresh_token(
+                    &self.credentials.application_name,
+                    &self.http_client,
+                    &request,
+                ),
+            )
+            .await?;
+        #[cfg(feature = "metrics")]
+        telemetry::record_token_refresh("customer");
+        self.customer_tokens
+            .insert(customer_id.to_string(), token.clone());
+        Ok(token)
+    }
+
+    // Restore a customer token persisted by the caller (e.g. after a restart)
+    pub fn set_customer_access_token(&mut self, customer_id: &String, token: AccessToken) {
+        self.customer_tokens.insert(customer_id.to_string(), token);
+    }
+
+    pub fn remove_customer_access_token(&mut self, customer_id: &String) -> Option<AccessToken> {
+        self.customer_tokens.remove(customer_id)
+    }
+
+    // Read the profile of a customer who has granted consent, using the customer's token
+    #[instrument(skip_all, fields(customer_id = %customer_id))]
+    pub async fn get_customer_profile(
+        &mut self,
+        customer_id: &String,
+    ) -> Result<CustomerProfile, SCBAPIError> {
+        let user_token = self.get_customer_access_token(customer_id).await?;
+        let client = self.http_client.clone();
+        let application_key = self.credentials.application_key.clone();
+
+        self.pipeline
+            .run(
+                Endpoint::Customer,
+                profile::get_customer_profile(&application_key, &client, &user_token),
+            )
+            .await
+            .map(|res| res.profile)
+    }
+
+    pub fn set_ref3_prefix(&mut self, ref3_prefix: &str) {
+        self.ref3_prefix = Some(ref3_prefix.to_string());
+    }
+
+    pub fn set_biller_id(&mut self, biller_id: &str) {
+        self.biller_id = Some(biller_id.to_string());
+    }
+
+    pub fn biller_id(&self) -> Option<&str> {
+        self.biller_id.as_deref()
+    }
+
+    // Generator of ref3 values starting with the configured Reference 3 Prefix,
+    // SCB rejects a ref3 without it
+    pub fn ref3_generator(&self) -> Result<ReferenceGenerator, &'static str> {
+        let ref3_prefix = self
+            .ref3_prefix
+            .as_deref()
+            .filter(|prefix| !prefix.is_empty())
+            .ok_or("ref3 prefix is not set")?;
+        ReferenceGeneratorBuilder::new()
+            .add_prefix(ref3_prefix)
+            .build()
+    }
+
+    pub fn set_payment_store(&mut self, payment_store: Arc<dyn PaymentStore>) {
+        self.payment_store = Some(payment_store);
+    }
+
+    #[instrument(skip_all)]
     pub async fn qr_code_create(
         &mut self,
         qr_code_params: &QRCodeRequest,
     ) -> Result<QRCodeResponse, SCBAPIError> {
         self.get_access_token_if_need().await?;
         debug!("Request: {:#?}", qr_code_params);
-        let client = create_client();
+        let client = self.http_client.clone();
         let access_token = self.access_token.as_ref().unwrap();
-        let application_key = self.application_key.clone();
+        let application_key = self.credentials.application_key.clone();
+        let request_uid = Uuid::new_v4().to_string();
+        let created_at = chrono::Utc::now().timestamp();
+
+        let res = self
+            .pipeline
+            .run(
+                Endpoint::QRCodeCreate,
+                qr_code_create(
+                    &application_key,
+                    &client,
+                    access_token,
+                    qr_code_params,
+                    &request_uid,
+                ),
+            )
+            .await;
+        if let Some(store) = self.payment_store.clone() {
+            let record = PaymentRecord {
+                request_uid,
+                qr_type: qr_code_params.qr_type().to_string(),
+                amount: qr_code_params.amount().to_string(),
+                pp_id: qr_code_params.pp_id().map(|s| s.to_string()),
+                ref1: qr_code_params.ref1().map(|s| s.to_string()),
+                ref2: qr_code_params.ref2().map(|s| s.to_string()),
+                ref3: qr_code_params.ref3().map(|s| s.to_string()),
+                invoice: qr_code_params.invoice().map(|s| s.to_string()),
+                qr_code_id: res.as_ref().ok().and_then(|r| r.qr_code_id.clone()),
+                request: serde_json::to_string(qr_code_params).unwrap_or_default(),
+                response: res
+                    .as_ref()
+                    .ok()
+                    .and_then(|r| serde_json::to_string(r).ok()),
+                error: res.as_ref().err().map(|e| e.to_string()),
+                status: if res.is_ok() {
+                    PaymentStatus::Created
+                } else {
+                    PaymentStatus::Failed
+                },
+                created_at,
+                updated_at: chrono::Utc::now().timestamp(),
+            };
+            // The QR exists at SCB at this point, so a store failure is logged instead of
+            // hiding the response from the caller. Stores may do blocking IO, e.g. SQLite
+            let request_uid = record.request_uid.clone();
+            match tokio::task::spawn_blocking(move || store.save(&record)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => error!("Failed to store payment {}: {}", request_uid, e),
+                Err(e) => error!("Failed to store payment {}: {}", request_uid, e),
+            }
+        }
+        res
+    }
 
-        qr_code_create(&application_key, &client, access_token, qr_code_params).await
+    // Look up the credit card transaction of a QR CS / PPCS by qrCodeId from qr_code_create
+    #[instrument(skip_all, fields(qr_code_id))]
+    pub async fn get_qr_cs_transaction(
+        &mut self,
+        qr_code_id: &str,
+    ) -> Result<QRCreditCardTransaction, SCBAPIError> {
+        self.get_access_token_if_need().await?;
+        let client = self.http_client.clone();
+        let access_token = self.access_token.as_ref().unwrap();
+        let application_key = self.credentials.application_key.clone();
+
+        let transaction = self
+            .pipeline
+            .run(
+                Endpoint::QRCode,
+                qr_cs_transaction_inquiry(&application_key, &client, access_token, qr_code_id),
+            )
+            .await?;
+        let status = match transaction.status {
+            QRCreditCardStatus::Paid => Some(PaymentStatus::Paid),
+            QRCreditCardStatus::Cancelled => Some(PaymentStatus::Cancelled),
+            QRCreditCardStatus::Expired => Some(PaymentStatus::Expired),
+            _ => None,
+        };
+        if let Some(status) = status {
+            self.update_stored_payments(PaymentQuery::QRCodeId(qr_code_id.to_string()), status)
+                .await;
+        }
+        Ok(transaction)
     }
 
+    // Cancel a QR CS / PPCS which has not been paid, e.g. when the customer abandons checkout
+    #[instrument(skip_all, fields(qr_code_id))]
+    pub async fn qr_cs_cancel(
+        &mut self,
+        qr_code_id: &str,
+    ) -> Result<QRCodeCancelResponse, SCBAPIError> {
+        self.get_access_token_if_need().await?;
+        let client = self.http_client.clone();
+        let access_token = self.access_token.as_ref().unwrap();
+        let application_key = self.credentials.application_key.clone();
+
+        let res = self
+            .pipeline
+            .run(
+                Endpoint::QRCode,
+                qr_code_cancel(&application_key, &client, access_token, qr_code_id),
+            )
+            .await?;
+        self.update_stored_payments(
+            PaymentQuery::QRCodeId(qr_code_id.to_string()),
+            PaymentStatus::Cancelled,
+        )
+        .await;
+        Ok(res)
+    }
+
+    // Set the status of the stored payments matching the query, when a payment store is configured
+    async fn update_stored_payments(&self, query: PaymentQuery, status: PaymentStatus) {
+        self.update_stored_payments_where(query, status, |_| true)
+            .await
+    }
+
+    async fn update_stored_payments_where<F>(
+        &self,
+        query: PaymentQuery,
+        status: PaymentStatus,
+        filter: F,
+    ) where
+        F: Fn(&PaymentRecord) -> bool + Send + 'static,
+    {
+        let Some(store) = self.payment_store.clone() else {
+            return;
+        };
+        // Stores may do blocking IO, e.g. SQLite
+        let updated = tokio::task::spawn_blocking(move || {
+            let records = match store.find(&query) {
+                Ok(records) => records,
+                Err(e) => {
+                    error!("Failed to find stored payments {:?}: {}", query, e);
+                    return;
+                }
+            };
+            for record in records.iter().filter(|r| filter(r)) {
+                if let Err(e) = store.update_status(&record.request_uid, status.clone()) {
+                    error!("Failed to update payment {}: {}", record.request_uid, e);
+                }
+            }
+        })
+        .await;
+        if let Err(e) = updated {
+            error!("Failed to update stored payments: {}", e);
+        }
+    }
+
+    #[instrument(skip_all, fields(trans_ref = %trans_ref, sending_bank = sending_bank.code()))]
     pub async fn get_slip_verification_qr30(
         &mut self,
         trans_ref: &String,
-        sending_bank: &String,
+        sending_bank: &BankCode,
     ) -> Result<BillPaymentTransactionSlip, SCBAPIError> {
         self.get_access_token_if_need().await?;
-        let client = create_client();
+        let client = self.http_client.clone();
         let access_token = self.access_token.as_ref().unwrap();
-        let application_key = self.application_key.clone();
-
-        bill_pay::get_bill_payment_transaction(
-            &application_key,
-            &client,
-            access_token,
-            trans_ref,
-            sending_bank,
-        )
+        let application_key = self.credentials.application_key.clone();
+
+        self.pipeline
+            .run(
+                Endpoint::SlipVerification,
+                bill_pay::get_bill_payment_transaction(
+                    &application_key,
+                    &client,
+                    access_token,
+                    trans_ref,
+                    sending_bank,
+                ),
+            )
+            .await
+    }
+
+    pub fn set_slip_registry(&mut self, slip_registry: Arc<dyn SlipRegistry>) {
+        self.slip_registry = Some(slip_registry);
+    }
+
+    // Verify a slip with SCB and claim it for the order in the slip registry.
+    // Fails with SCBAPIError::DuplicateSlip if the slip was already used for another order,
+    // and with SCBAPIError::StorageError when no slip registry is set.
+    #[instrument(skip_all, fields(trans_ref = %trans_ref, sending_bank = sending_bank.code(), order_id))]
+    pub async fn get_slip_verification_for_order(
+        &mut self,
+        trans_ref: &String,
+        sending_bank: &BankCode,
+        order_id: &str,
+    ) -> Result<BillPaymentTransactionSlip, SCBAPIError> {
+        let registry = self.slip_registry.clone().ok_or_else(|| {
+            SCBAPIError::StorageError(
+                "no slip registry is set, slips cannot be claimed for orders".to_string(),
+            )
+        })?;
+        let slip = self
+            .get_slip_verification_qr30(trans_ref, sending_bank)
+            .await?;
+
+        // Registries may do blocking IO, e.g. SQLite
+        let (slip_trans_ref, slip_bank, order) = (
+            slip.trans_ref.clone(),
+            slip.sending_bank.code().to_string(),
+            order_id.to_string(),
+        );
+        let claim = tokio::task::spawn_blocking(move || {
+            registry.claim(&slip_trans_ref, &slip_bank, &order)
+        })
         .await
+        .map_err(|e| SCBAPIError::StorageError(e.to_string()))??;
+        debug!(
+            "Slip {} claimed for order {} at {}",
+            claim.trans_ref, claim.order_id, claim.claimed_at
+        );
+        Ok(slip)
     }
 
+    // Verify a slip by the payload of the mini QR printed on it
+    #[instrument(skip_all, fields(trans_ref = %slip_qr.trans_ref))]
+    pub async fn get_slip_verification_from_qr(
+        &mut self,
+        slip_qr: &SlipQRCode,
+    ) -> Result<BillPaymentTransactionSlip, SCBAPIError> {
+        self.get_slip_verification_qr30(&slip_qr.trans_ref, &slip_qr.sending_bank)
+            .await
+    }
+
+    #[instrument(skip_all)]
     pub async fn query_bill_payment_transaction(
         &mut self,
         params: &BillPaymentInquiryRequest,
     ) -> Result<Vec<BillPaymentTransaction>, SCBAPIError> {
         self.get_access_token_if_need().await?;
-        let client = create_client();
+        let client = self.http_client.clone();
         let access_token = self.access_token.as_ref().unwrap();
-        let application_key = self.application_key.clone();
+        let application_key = self.credentials.application_key.clone();
 
-        bill_pay::query_bill_payment_transaction(&application_key, &client, access_token, params)
+        self.pipeline
+            .run(
+                Endpoint::Inquiry,
+                bill_pay::query_bill_payment_transaction(
+                    &application_key,
+                    &client,
+                    access_token,
+                    params,
+                ),
+            )
             .await
     }
 
+    // Stream the C scan B payments of these reference1 to several billers over a date range,
+    // with at most `concurrency` inquiries in flight. A failed inquiry is yielded as an error item.
+    #[instrument(skip_all, fields(%from, %to))]
+    pub async fn query_bill_payment_transaction_stream<S: AsRef<str>, R: AsRef<str>>(
+        &mut self,
+        biller_ids: &[S],
+        references1: &[R],
+        from: NaiveDate,
+        to: NaiveDate,
+        concurrency: usize,
+    ) -> Result<
+        BoxStream<'static, Result<BillPaymentTransaction, BillPaymentInquiryError>>,
+        SCBAPIError,
+    > {
+        self.get_access_token_if_need().await?;
+        let client = self.http_client.clone();
+        let token_source = ApplicationTokenSource::new(
+            self.credentials.clone(),
+            client.clone(),
+            self.pipeline.clone(),
+            self.access_token.clone().unwrap(),
+        );
+        let application_key = self.credentials.application_key.clone();
+
+        let requests =
+            BillPaymentInquiryRequest::for_billers_between(biller_ids, references1, from, to);
+        Ok(bill_pay::query_bill_payment_transaction_stream(
+            self.pipeline.clone(),
+            application_key,
+            client,
+            token_source,
+            requests,
+            concurrency,
+        ))
+    }
+
+    // Reconcile expected payments against the C scan B payments received by a biller
+    // from `from` to `to` inclusive. SCB is inquired by the reference1 of the expected payments,
+    // so unexpected transactions are extra payments of those references.
+    #[instrument(skip_all, fields(biller_id, %from, %to))]
+    pub async fn reconcile_bill_payments(
+        &mut self,
+        biller_id: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+        expected: &[ExpectedPayment],
+    ) -> Result<ReconciliationReport, SCBAPIError> {
+        let mut references1: Vec<&str> = expected.iter().map(|p| p.reference1.as_str()).collect();
+        references1.sort_unstable();
+        references1.dedup();
+        let mut transactions = Vec::new();
+        for params in
+            BillPaymentInquiryRequest::for_billers_between(&[biller_id], &references1, from, to)
+        {
+            transactions.extend(self.query_bill_payment_transaction(&params).await?);
+        }
+        let mut report = reconcile(expected, &transactions);
+        report.from = Some(from.format("%Y-%m-%d").to_string());
+        report.to = Some(to.format("%Y-%m-%d").to_string());
+        Ok(report)
+    }
+
+    // Poll bill payment inquiry with backoff until the QR with these references is paid
+    // or the deadline passes
+    pub async fn wait_for_payment(
+        &mut self,
+        biller_id: &str,
+        reference1: &str,
+        reference2: Option<&str>,
+        amount: &str,
+        deadline: DateTime<Utc>,
+    ) -> PaymentOutcome {
+        let mut session = PaymentSession::new(biller_id, reference1, reference2, amount, deadline);
+        self.wait_for_payment_session(&mut session, None).await
+    }
+
+    // Drive a payment session to Paid or Expired. Transactions received on `webhook`, e.g.
+    // forwarded from the payment confirmation callback, resolve the session without waiting
+    // for the next inquiry. HTTP errors, rate limits and open circuits are retried
+    // until the deadline, where a last inquiry decides between Paid and Expired.
+    // Stored payments with the same biller id, references and amount are marked Paid or Expired.
+    #[instrument(skip_all, fields(biller_id = %session.biller_id, reference1 = %session.reference1))]
+    pub async fn wait_for_payment_session(
+        &mut self,
+        session: &mut PaymentSession,
+        webhook: Option<&mut mpsc::Receiver<BillPaymentTransaction>>,
+    ) -> PaymentOutcome {
+        let outcome = poll_payment_session(self, session, webhook).await;
+        let status = match &outcome {
+            PaymentOutcome::Paid(_) => PaymentStatus::Paid,
+            PaymentOutcome::Expired => PaymentStatus::Expired,
+            PaymentOutcome::Error(_) => return outcome,
+        };
+        let stored_session = session.clone();
+        self.update_stored_payments_where(
+            PaymentQuery::Biller {
+                pp_id: session.biller_id.clone(),
+                ref1: session.reference1.clone(),
+            },
+            status,
+            move |record| is_session_record(&stored_session, record),
+        )
+        .await;
+        outcome
+    }
+
+    // Charge a customer presented QR (My Prompt QR, B scan C)
+    #[instrument(skip_all)]
+    pub async fn b_scan_c_payment(
+        &mut self,
+        payment_request: &BScanCPaymentRequest,
+    ) -> Result<BScanCPaymentResponse, SCBAPIError> {
+        self.get_access_token_if_need().await?;
+        let client = self.http_client.clone();
+        let access_token = self.access_token.as_ref().unwrap();
+        let application_key = self.credentials.application_key.clone();
+
+        self.pipeline
+            .run(
+                Endpoint::BScanC,
+                b_scan_c::b_scan_c_payment(
+                    &application_key,
+                    &client,
+                    access_token,
+                    payment_request,
+                ),
+            )
+            .await
+    }
+
+    // Void a B scan C payment by the partner transaction id used in b_scan_c_payment
+    #[instrument(skip_all, fields(partner_transaction_id))]
+    pub async fn b_scan_c_void(
+        &mut self,
+        partner_transaction_id: &str,
+        transaction_id: Option<&str>,
+    ) -> Result<BScanCVoidResponse, SCBAPIError> {
+        self.get_access_token_if_need().await?;
+        let client = self.http_client.clone();
+        let access_token = self.access_token.as_ref().unwrap();
+        let application_key = self.credentials.application_key.clone();
+
+        let void_request = BScanCVoidRequest {
+            partner_transaction_id: partner_transaction_id.to_string(),
+            transaction_id: transaction_id.map(|s| s.to_string()),
+        };
+        self.pipeline
+            .run(
+                Endpoint::BScanC,
+                b_scan_c::b_scan_c_void(&application_key, &client, access_token, &void_request),
+            )
+            .await
+    }
+
+    // Look up a B scan C payment through bill payment inquiry (event code 00300104)
+    #[instrument(skip_all, fields(partner_transaction_id))]
+    pub async fn query_b_scan_c_payment(
+        &mut self,
+        transaction_date: &str,
+        partner_transaction_id: &str,
+    ) -> Result<Vec<BillPaymentTransaction>, SCBAPIError> {
+        let params =
+            BillPaymentInquiryRequest::for_b_scan_c(transaction_date, partner_transaction_id);
+        self.query_bill_payment_transaction(&params).await
+    }
+
     async fn get_access_token_if_need(&mut self) -> Result<(), SCBAPIError> {
         if self.access_token.is_none() {
             let req = self.request_access_token().await;
@@ -146,3 +864,277 @@ impl SCBClientAPI {
         Ok(())
     }
 }
+
+// Created record of the QR the session waits for
+fn is_session_record(session: &PaymentSession, record: &PaymentRecord) -> bool {
+    record.status == PaymentStatus::Created
+        && record.ref1.as_deref() == Some(session.reference1.as_str())
+        && (session.reference2.is_none() || record.ref2 == session.reference2)
+        && record.pp_id.as_deref() == Some(session.biller_id.as_str())
+        && session.matches_amount(&record.amount)
+}
+
+// Inquiry of the payment of a session, separate from the client so polling can be tested
+trait SessionInquiry {
+    async fn find_session_payment(
+        &mut self,
+        session: &PaymentSession,
+    ) -> Result<Option<BillPaymentTransaction>, SCBAPIError>;
+}
+
+impl SessionInquiry for SCBClientAPI {
+    async fn find_session_payment(
+        &mut self,
+        session: &PaymentSession,
+    ) -> Result<Option<BillPaymentTransaction>, SCBAPIError> {
+        for params in session.inquiry_requests(Utc::now()) {
+            let transactions = self.query_bill_payment_transaction(&params).await?;
+            if let Some(transaction) = transactions.into_iter().find(|t| session.matches(t)) {
+                return Ok(Some(transaction));
+            }
+        }
+        Ok(None)
+    }
+}
+
+// The session only expires after an inquiry made at or after the deadline, so a payment made
+// during the last backoff is still found
+async fn poll_payment_session<I: SessionInquiry>(
+    inquiry: &mut I,
+    session: &mut PaymentSession,
+    mut webhook: Option<&mut mpsc::Receiver<BillPaymentTransaction>>,
+) -> PaymentOutcome {
+    match session.state() {
+        PaymentSessionState::Paid(transaction) => return PaymentOutcome::Paid(transaction.clone()),
+        PaymentSessionState::Expired => return PaymentOutcome::Expired,
+        _ => {}
+    }
+    let mut attempt = 0;
+    loop {
+        session.mark_pending();
+        let inquired_at = Utc::now();
+        match inquiry.find_session_payment(session).await {
+            Ok(Some(transaction)) => {
+                session.mark_paid(transaction.clone());
+                return PaymentOutcome::Paid(Box::new(transaction));
+            }
+            Ok(None) => {}
+            // The last inquiry is not retried, its failure is returned instead of Expired
+            Err(
+                e @ (SCBAPIError::HttpRequestError(_)
+                | SCBAPIError::ServerError { .. }
+                | SCBAPIError::InvalidResponse(_)
+                | SCBAPIError::RateLimited { .. }
+                | SCBAPIError::CircuitOpen { .. }),
+            ) if inquired_at < session.deadline => {
+                error!("Payment inquiry failed, retrying: {}", e);
+                #[cfg(feature = "metrics")]
+                telemetry::record_retry(Endpoint::Inquiry);
+            }
+            Err(e) => return PaymentOutcome::Error(e),
+        }
+        if session.expire_if_due(inquired_at) {
+            return PaymentOutcome::Expired;
+        }
+
+        let remaining = (session.deadline - Utc::now()).to_std().unwrap_or_default();
+        let sleep = tokio::time::sleep(session.backoff.delay(attempt).min(remaining));
+        tokio::pin!(sleep);
+        attempt += 1;
+        loop {
+            let Some(receiver) = webhook.as_deref_mut() else {
+                (&mut sleep).await;
+                break;
+            };
+            tokio::select! {
+                _ = &mut sleep => break,
+                received = receiver.recv() => match received {
+                    Some(transaction) if session.matches(&transaction) => {
+                        session.mark_paid(transaction.clone());
+                        return PaymentOutcome::Paid(Box::new(transaction));
+                    }
+                    Some(_) => {}
+                    None => webhook = None,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frameworks::credentials::StaticCredentialProvider;
+
+    // Paid only from the inquiry made at the deadline
+    struct PaidAtDeadline {
+        inquiries: u32,
+    }
+
+    impl SessionInquiry for PaidAtDeadline {
+        async fn find_session_payment(
+            &mut self,
+            session: &PaymentSession,
+        ) -> Result<Option<BillPaymentTransaction>, SCBAPIError> {
+            self.inquiries += 1;
+            if Utc::now() < session.deadline {
+                return Ok(None);
+            }
+            Ok(Some(
+                serde_json::from_value(serde_json::json!({
+                    "transactionId": "T1",
+                    "transactionDateandTime": "2023-01-01T12:00:00+07:00",
+                    "amount": "100.00",
+                    "billPaymentRef1": "REF1"
+                }))
+                .unwrap(),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_payment_found_at_deadline() {
+        let deadline = Utc::now() + chrono::Duration::milliseconds(100);
+        let mut session = PaymentSession::new("123456789012345", "REF1", None, "100.00", deadline);
+        let mut inquiry = PaidAtDeadline { inquiries: 0 };
+        let outcome = poll_payment_session(&mut inquiry, &mut session, None).await;
+        assert!(matches!(outcome, PaymentOutcome::Paid(_)));
+        assert_eq!(inquiry.inquiries, 2);
+    }
+
+    // Fails every inquiry as an unreachable gateway would
+    struct Unreachable {
+        inquiries: u32,
+    }
+
+    impl SessionInquiry for Unreachable {
+        async fn find_session_payment(
+            &mut self,
+            _: &PaymentSession,
+        ) -> Result<Option<BillPaymentTransaction>, SCBAPIError> {
+            self.inquiries += 1;
+            Err(SCBAPIError::InvalidResponse("<html>".to_string()))
+        }
+    }
+
+    // A session past its deadline is still inquired once, its failure is returned
+    #[tokio::test]
+    async fn test_payment_past_deadline() {
+        let deadline = Utc::now() - chrono::Duration::seconds(1);
+        let mut session = PaymentSession::new("123456789012345", "REF1", None, "100.00", deadline);
+        let mut inquiry = Unreachable { inquiries: 0 };
+        let outcome = poll_payment_session(&mut inquiry, &mut session, None).await;
+        assert!(matches!(
+            outcome,
+            PaymentOutcome::Error(SCBAPIError::InvalidResponse(_))
+        ));
+        assert_eq!(inquiry.inquiries, 1);
+    }
+
+    #[test]
+    fn test_is_session_record() {
+        let session = PaymentSession::new("123456789012345", "REF1", None, "100.00", Utc::now());
+        let record = |pp_id: &str, amount: &str| PaymentRecord {
+            request_uid: "UID1".to_string(),
+            qr_type: "PP".to_string(),
+            amount: amount.to_string(),
+            pp_id: Some(pp_id.to_string()),
+            ref1: Some("REF1".to_string()),
+            ref2: None,
+            ref3: None,
+            invoice: None,
+            qr_code_id: None,
+            request: "{}".to_string(),
+            response: None,
+            error: None,
+            status: PaymentStatus::Created,
+            created_at: 0,
+            updated_at: 0,
+        };
+        assert!(is_session_record(
+            &session,
+            &record("123456789012345", "100")
+        ));
+        assert!(!is_session_record(
+            &session,
+            &record("999999999999999", "100")
+        ));
+        assert!(!is_session_record(
+            &session,
+            &record("123456789012345", "50")
+        ));
+    }
+
+    #[test]
+    fn test_ref3_generator_requires_prefix() {
+        let mut client = SCBClientAPI::new("app", "key", "secret");
+        assert_eq!(
+            client.ref3_generator().err(),
+            Some("ref3 prefix is not set")
+        );
+        client.set_ref3_prefix("");
+        assert_eq!(
+            client.ref3_generator().err(),
+            Some("ref3 prefix is not set")
+        );
+        client.set_ref3_prefix("ABC");
+        assert!(client
+            .ref3_generator()
+            .unwrap()
+            .generate()
+            .starts_with("ABC"));
+    }
+
+    #[tokio::test]
+    async fn test_slip_for_order_requires_registry() {
+        let mut client = SCBClientAPI::new("app", "key", "secret");
+        let result = client
+            .get_slip_verification_for_order(&"TREF1".to_string(), &BankCode::SCB, "ORDER1")
+            .await;
+        assert!(matches!(result, Err(SCBAPIError::StorageError(_))));
+    }
+
+    #[test]
+    fn test_serialized_client_has_no_secret() {
+        let client = SCBClientAPI::new("app", "key", "top-secret");
+        let json = serde_json::to_string(&client).unwrap();
+        assert!(json.contains("\"application_key\":\"key\""));
+        assert!(!json.contains("top-secret"));
+    }
+
+    #[tokio::test]
+    async fn test_changed_credentials_drop_token() {
+        let token = AccessToken {
+            access_token: "token".to_string(),
+            token_type: "Bearer".to_string(),
+            expires_in: 1800,
+            expires_at: Utc::now().timestamp() + 1800,
+            refresh_token: None,
+            refresh_expires_in: None,
+            refresh_expires_at: None,
+        };
+        let customer_id = "CUST1".to_string();
+        let mut client = SCBClientAPI::new("app", "key", "old");
+        client.access_token = Some(token.clone());
+        client.set_customer_access_token(&customer_id, token.clone());
+        let set_provider = |client: &mut SCBClientAPI, credentials| {
+            client.set_credential_provider(Arc::new(StaticCredentialProvider::new(credentials)));
+        };
+
+        set_provider(&mut client, Credentials::new("app", "key", "old"));
+        client.refresh_credentials().await.unwrap();
+        assert!(client.access_token.is_some());
+
+        set_provider(&mut client, Credentials::new("app", "key", "new"));
+        client.refresh_credentials().await.unwrap();
+        assert!(client.access_token.is_none());
+        assert_eq!(client.credentials.secret_key, "new");
+        assert!(client.customer_tokens.contains_key(&customer_id));
+
+        client.access_token = Some(token);
+        set_provider(&mut client, Credentials::new("app", "other-key", "new"));
+        client.refresh_credentials().await.unwrap();
+        assert!(client.access_token.is_none());
+        assert!(client.customer_tokens.is_empty());
+    }
+}
@@ -0,0 +1,148 @@
+use std::cell::Cell;
+use std::time::Duration;
+
+use metrics::{counter, describe_counter, describe_histogram, histogram, Unit};
+
+use crate::errors::scb_error::SCBAPIError;
+use crate::frameworks::apis::pipeline::Endpoint;
+
+pub const REQUESTS_TOTAL: &str = "scb_requests_total";
+pub const REQUEST_DURATION_SECONDS: &str = "scb_request_duration_seconds";
+pub const STATUS_CODES_TOTAL: &str = "scb_status_codes_total";
+pub const TOKEN_REFRESHES_TOTAL: &str = "scb_token_refreshes_total";
+pub const RETRIES_TOTAL: &str = "scb_retries_total";
+pub const RATE_LIMIT_WAIT_SECONDS: &str = "scb_rate_limit_wait_seconds";
+
+tokio::task_local! {
+    // status.code of the SCB response of the request running in the pipeline, set by map_result
+    pub(crate) static SCB_STATUS: Cell<Option<i32>>;
+}
+
+// Register units and descriptions with the installed recorder, e.g. for Prometheus HELP lines
+pub fn describe_metrics() {
+    describe_counter!(REQUESTS_TOTAL, "SCB API requests by endpoint and outcome");
+    describe_histogram!(
+        REQUEST_DURATION_SECONDS,
+        Unit::Seconds,
+        "Latency of SCB API requests by endpoint"
+    );
+    describe_counter!(
+        STATUS_CODES_TOTAL,
+        "status.code of SCB API responses by endpoint"
+    );
+    describe_counter!(
+        TOKEN_REFRESHES_TOTAL,
+        "Access tokens requested or refreshed, application or customer"
+    );
+    describe_counter!(RETRIES_TOTAL, "Requests retried by the SDK by endpoint");
+    describe_histogram!(
+        RATE_LIMIT_WAIT_SECONDS,
+        Unit::Seconds,
+        "Time spent waiting for the client side rate limiter by endpoint"
+    );
+}
+
+// `latency` is None for requests rejected before being sent
+pub(crate) fn record_request(
+    endpoint: Endpoint,
+    error: Option<&SCBAPIError>,
+    latency: Option<Duration>,
+    scb_status: Option<i32>,
+) {
+    let outcome = match error {
+        None => "success",
+        Some(SCBAPIError::HttpRequestError(_)) => "http_error",
+        Some(SCBAPIError::RateLimited { .. }) => "rate_limited",
+        Some(SCBAPIError::CircuitOpen { .. }) => "circuit_open",
+        Some(SCBAPIError::ServerError { .. }) => "server_error",
+        Some(SCBAPIError::InvalidResponse(_)) => "invalid_response",
+        Some(_) => "scb_error",
+    };
+    counter!(REQUESTS_TOTAL, "endpoint" => endpoint.to_string(), "outcome" => outcome).increment(1);
+    if let Some(latency) = latency {
+        histogram!(REQUEST_DURATION_SECONDS, "endpoint" => endpoint.to_string())
+            .record(latency.as_secs_f64());
+    }
+    if let Some(code) = scb_status {
+        counter!(STATUS_CODES_TOTAL, "endpoint" => endpoint.to_string(), "code" => code.to_string())
+            .increment(1);
+    }
+}
+
+// `token` is application or customer
+pub(crate) fn record_token_refresh(token: &'static str) {
+    counter!(TOKEN_REFRESHES_TOTAL, "token" => token).increment(1);
+}
+
+pub(crate) fn record_retry(endpoint: Endpoint) {
+    counter!(RETRIES_TOTAL, "endpoint" => endpoint.to_string()).increment(1);
+}
+
+pub(crate) fn record_rate_limit_wait(endpoint: Endpoint, wait: Duration) {
+    histogram!(RATE_LIMIT_WAIT_SECONDS, "endpoint" => endpoint.to_string())
+        .record(wait.as_secs_f64());
+}
+
+#[cfg(test)]
+mod tests {
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use metrics_util::MetricKind;
+
+    use super::*;
+    use crate::frameworks::apis::pipeline::RequestPipeline;
+
+    #[test]
+    fn test_pipeline_records_metrics() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        metrics::with_local_recorder(&recorder, || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_time()
+                .build()
+                .unwrap();
+            runtime.block_on(async {
+                let pipeline = RequestPipeline::default();
+                let ok = pipeline
+                    .run(Endpoint::Inquiry, async {
+                        SCB_STATUS.with(|s| s.set(Some(1000)));
+                        Ok::<_, SCBAPIError>(())
+                    })
+                    .await;
+                assert!(ok.is_ok());
+                let err = pipeline
+                    .run(Endpoint::Inquiry, async {
+                        Err::<(), _>(SCBAPIError::SCBError("Invalid".to_string()))
+                    })
+                    .await;
+                assert!(err.is_err());
+            });
+        });
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let counter = |name: &str, label: (&str, &str)| {
+            snapshot
+                .iter()
+                .find(|(key, _, _, _)| {
+                    key.kind() == MetricKind::Counter
+                        && key.key().name() == name
+                        && key
+                            .key()
+                            .labels()
+                            .any(|l| l.key() == label.0 && l.value() == label.1)
+                })
+                .map(|(_, _, _, value)| value)
+        };
+        assert_eq!(
+            counter(REQUESTS_TOTAL, ("outcome", "success")),
+            Some(&DebugValue::Counter(1))
+        );
+        assert_eq!(
+            counter(REQUESTS_TOTAL, ("outcome", "scb_error")),
+            Some(&DebugValue::Counter(1))
+        );
+        assert_eq!(
+            counter(STATUS_CODES_TOTAL, ("code", "1000")),
+            Some(&DebugValue::Counter(1))
+        );
+    }
+}
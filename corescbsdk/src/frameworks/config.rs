@@ -0,0 +1,466 @@
+#[cfg(feature = "config-file")]
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(feature = "config-file")]
+use std::path::Path;
+
+use serde::Deserialize;
+use zeroize::Zeroize;
+
+use crate::frameworks::credentials::Credentials;
+
+// Environment variables read by SCBConfigBuilder::add_env
+pub const ENV_APPLICATION_NAME: &str = "APP_NAME";
+pub const ENV_APPLICATION_KEY: &str = "APP_KEY";
+pub const ENV_SECRET_KEY: &str = "APP_SECRET";
+pub const ENV_BILLER_ID: &str = "BILLER_ID";
+pub const ENV_REF3_PREFIX: &str = "REF_3PREFIX";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SCBConfigError {
+    // Required values not set by any source, e.g. "application_key (APP_KEY)"
+    MissingFields(Vec<&'static str>),
+    InvalidField { field: &'static str, reason: String },
+    ProfileNotFound(String),
+    File { path: String, reason: String },
+    // A credential command failed or printed no usable profile
+    Command { command: String, reason: String },
+}
+
+impl fmt::Display for SCBConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SCBConfigError::MissingFields(fields) => write!(f, "missing {}", fields.join(", ")),
+            SCBConfigError::InvalidField { field, reason } => {
+                write!(f, "invalid {}: {}", field, reason)
+            }
+            SCBConfigError::ProfileNotFound(name) => write!(f, "profile {} not found", name),
+            SCBConfigError::File { path, reason } => write!(f, "{}: {}", path, reason),
+            SCBConfigError::Command { command, reason } => write!(f, "{}: {}", command, reason),
+        }
+    }
+}
+
+impl std::error::Error for SCBConfigError {}
+
+// Credentials of an SCB application and the defaults of its merchant
+#[derive(Clone, PartialEq, Eq)]
+pub struct SCBConfig {
+    pub application_name: String,
+    pub application_key: String,
+    pub secret_key: String,
+    // Default biller id for QR 30 and bill payment inquiries
+    pub biller_id: Option<String>,
+    // Reference 3 Prefix of the merchant profile
+    pub ref3_prefix: Option<String>,
+}
+
+// The secret key is left out so configs can be logged
+impl fmt::Debug for SCBConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SCBConfig")
+            .field("application_name", &self.application_name)
+            .field("application_key", &self.application_key)
+            .field("secret_key", &"***")
+            .field("biller_id", &self.biller_id)
+            .field("ref3_prefix", &self.ref3_prefix)
+            .finish()
+    }
+}
+
+impl Drop for SCBConfig {
+    fn drop(&mut self) {
+        self.application_key.zeroize();
+        self.secret_key.zeroize();
+    }
+}
+
+impl SCBConfig {
+    pub fn from_env() -> Result<SCBConfig, SCBConfigError> {
+        SCBConfigBuilder::new().add_env().build()
+    }
+
+    // The named profile of the file, or its default_profile when no name is given
+    #[cfg(feature = "config-file")]
+    pub fn from_file(
+        path: impl AsRef<Path>,
+        profile: Option<&str>,
+    ) -> Result<SCBConfig, SCBConfigError> {
+        SCBConfigBuilder::new().add_file(path, profile)?.build()
+    }
+}
+
+// Values of one profile, unset values are left to the other sources
+#[derive(Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SCBProfile {
+    pub application_name: Option<String>,
+    pub application_key: Option<String>,
+    pub secret_key: Option<String>,
+    pub biller_id: Option<String>,
+    pub ref3_prefix: Option<String>,
+}
+
+// As SCBConfig, shows whether the secret key is set but not its value. SCBConfigFile and
+// SCBConfigBuilder print their profiles through this.
+impl fmt::Debug for SCBProfile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SCBProfile")
+            .field("application_name", &self.application_name)
+            .field("application_key", &self.application_key)
+            .field("secret_key", &self.secret_key.as_ref().map(|_| "***"))
+            .field("biller_id", &self.biller_id)
+            .field("ref3_prefix", &self.ref3_prefix)
+            .finish()
+    }
+}
+
+impl Drop for SCBProfile {
+    fn drop(&mut self) {
+        self.application_key.zeroize();
+        self.secret_key.zeroize();
+    }
+}
+
+// TOML
+//
+// default_profile = "sandbox"
+//
+// [profiles.sandbox]
+// application_name = "..."
+// application_key = "..."
+// secret_key = "..."
+//
+// [profiles.prod-shop-a]
+// ...
+// biller_id = "..."
+// ref3_prefix = "..."
+//
+// or the same structure in YAML
+#[cfg(feature = "config-file")]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SCBConfigFile {
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: HashMap<String, SCBProfile>,
+}
+
+#[cfg(feature = "config-file")]
+impl SCBConfigFile {
+    pub fn from_toml(content: &str) -> Result<SCBConfigFile, String> {
+        toml::from_str(content).map_err(|e| e.to_string())
+    }
+
+    pub fn from_yaml(content: &str) -> Result<SCBConfigFile, String> {
+        serde_yaml::from_str(content).map_err(|e| e.to_string())
+    }
+
+    // The format is chosen by the extension: .toml, .yaml or .yml
+    pub fn load(path: impl AsRef<Path>) -> Result<SCBConfigFile, SCBConfigError> {
+        let path = path.as_ref();
+        let file_error = |reason: String| SCBConfigError::File {
+            path: path.display().to_string(),
+            reason,
+        };
+        let mut content = std::fs::read_to_string(path).map_err(|e| file_error(e.to_string()))?;
+        let file = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            Some("yaml") | Some("yml") => Self::from_yaml(&content),
+            _ => Err("expected a .toml, .yaml or .yml file".to_string()),
+        };
+        content.zeroize();
+        file.map_err(file_error)
+    }
+
+    // The named profile, the default profile or an empty one when neither is set
+    pub fn profile(&self, name: Option<&str>) -> Result<SCBProfile, SCBConfigError> {
+        match name.or(self.default_profile.as_deref()) {
+            Some(name) => self
+                .profiles
+                .get(name)
+                .cloned()
+                .ok_or_else(|| SCBConfigError::ProfileNotFound(name.to_string())),
+            None => Ok(SCBProfile::default()),
+        }
+    }
+}
+
+// Sources are applied in order, each value set by a later source replaces the earlier one,
+// e.g. add_env() then add_file(..) then add_biller_id(..)
+#[derive(Debug, Clone, Default)]
+pub struct SCBConfigBuilder {
+    values: SCBProfile,
+}
+
+impl SCBConfigBuilder {
+    pub fn new() -> SCBConfigBuilder {
+        SCBConfigBuilder::default()
+    }
+
+    // APP_NAME, APP_KEY, APP_SECRET, BILLER_ID and REF_3PREFIX, empty variables are ignored
+    pub fn add_env(&mut self) -> &mut Self {
+        self.add_vars(|name| std::env::var(name).ok())
+    }
+
+    fn add_vars(&mut self, var: impl Fn(&str) -> Option<String>) -> &mut Self {
+        let var = |name: &str| var(name).filter(|v| !v.is_empty());
+        self.add_profile(&SCBProfile {
+            application_name: var(ENV_APPLICATION_NAME),
+            application_key: var(ENV_APPLICATION_KEY),
+            secret_key: var(ENV_SECRET_KEY),
+            biller_id: var(ENV_BILLER_ID),
+            ref3_prefix: var(ENV_REF3_PREFIX),
+        })
+    }
+
+    #[cfg(feature = "config-file")]
+    pub fn add_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        profile: Option<&str>,
+    ) -> Result<&mut Self, SCBConfigError> {
+        let profile = SCBConfigFile::load(path)?.profile(profile)?;
+        Ok(self.add_profile(&profile))
+    }
+
+    pub fn add_profile(&mut self, profile: &SCBProfile) -> &mut Self {
+        let merge = |value: &mut Option<String>, other: &Option<String>| {
+            if other.is_some() {
+                value.clone_from(other);
+            }
+        };
+        merge(&mut self.values.application_name, &profile.application_name);
+        merge(&mut self.values.application_key, &profile.application_key);
+        merge(&mut self.values.secret_key, &profile.secret_key);
+        merge(&mut self.values.biller_id, &profile.biller_id);
+        merge(&mut self.values.ref3_prefix, &profile.ref3_prefix);
+        self
+    }
+
+    pub fn add_application_name(&mut self, application_name: &str) -> &mut Self {
+        self.values.application_name = Some(application_name.to_string());
+        self
+    }
+
+    pub fn add_application_key(&mut self, application_key: &str) -> &mut Self {
+        self.values.application_key = Some(application_key.to_string());
+        self
+    }
+
+    pub fn add_secret_key(&mut self, secret_key: &str) -> &mut Self {
+        self.values.secret_key = Some(secret_key.to_string());
+        self
+    }
+
+    pub fn add_biller_id(&mut self, biller_id: &str) -> &mut Self {
+        self.values.biller_id = Some(biller_id.to_string());
+        self
+    }
+
+    pub fn add_ref3_prefix(&mut self, ref3_prefix: &str) -> &mut Self {
+        self.values.ref3_prefix = Some(ref3_prefix.to_string());
+        self
+    }
+
+    // Only the credentials, the merchant defaults are neither required nor validated
+    pub fn build_credentials(&self) -> Result<Credentials, SCBConfigError> {
+        let values = &self.values;
+        let required = [
+            ("application_name (APP_NAME)", &values.application_name),
+            ("application_key (APP_KEY)", &values.application_key),
+            ("secret_key (APP_SECRET)", &values.secret_key),
+        ];
+        let missing: Vec<&'static str> = required
+            .iter()
+            .filter(|(_, value)| value.is_none())
+            .map(|(name, _)| *name)
+            .collect();
+        if !missing.is_empty() {
+            return Err(SCBConfigError::MissingFields(missing));
+        }
+        Ok(Credentials::new(
+            values.application_name.as_deref().unwrap(),
+            values.application_key.as_deref().unwrap(),
+            values.secret_key.as_deref().unwrap(),
+        ))
+    }
+
+    pub fn build(&self) -> Result<SCBConfig, SCBConfigError> {
+        let credentials = self.build_credentials()?;
+        let values = &self.values;
+        if let Some(biller_id) = &values.biller_id {
+            if biller_id.len() != 15 || !biller_id.chars().all(|c| c.is_ascii_digit()) {
+                return Err(SCBConfigError::InvalidField {
+                    field: "biller_id",
+                    reason: "must be 15 digits".to_string(),
+                });
+            }
+        }
+        if let Some(ref3_prefix) = &values.ref3_prefix {
+            if !ref3_prefix
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+            {
+                return Err(SCBConfigError::InvalidField {
+                    field: "ref3_prefix",
+                    reason: "must only contain A-Z and 0-9".to_string(),
+                });
+            }
+        }
+
+        Ok(SCBConfig {
+            application_name: credentials.application_name.clone(),
+            application_key: credentials.application_key.clone(),
+            secret_key: credentials.secret_key.clone(),
+            biller_id: values.biller_id.clone(),
+            ref3_prefix: values.ref3_prefix.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frameworks::apis::scb::SCBClientAPI;
+
+    #[test]
+    fn test_missing_fields() {
+        let result = SCBConfigBuilder::new().add_application_name("app").build();
+        assert_eq!(
+            result,
+            Err(SCBConfigError::MissingFields(vec![
+                "application_key (APP_KEY)",
+                "secret_key (APP_SECRET)"
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_sources_in_order() {
+        let config = SCBConfigBuilder::new()
+            .add_vars(|name| match name {
+                ENV_APPLICATION_NAME => Some("env-app".to_string()),
+                ENV_APPLICATION_KEY => Some("env-key".to_string()),
+                ENV_SECRET_KEY => Some("env-secret".to_string()),
+                ENV_REF3_PREFIX => Some(String::new()),
+                _ => None,
+            })
+            .add_profile(&SCBProfile {
+                application_name: None,
+                application_key: Some("profile-key".to_string()),
+                secret_key: None,
+                biller_id: None,
+                ref3_prefix: None,
+            })
+            .add_biller_id("123456789012345")
+            .build()
+            .unwrap();
+        assert_eq!(config.application_name, "env-app");
+        assert_eq!(config.application_key, "profile-key");
+        assert_eq!(config.biller_id.as_deref(), Some("123456789012345"));
+        assert_eq!(config.ref3_prefix, None);
+        assert!(!format!("{:?}", config).contains("env-secret"));
+    }
+
+    #[test]
+    fn test_debug_masks_secret() {
+        let mut profile = SCBProfile::default();
+        profile.secret_key = Some("profile-secret".to_string());
+        assert!(!format!("{:?}", profile).contains("profile-secret"));
+        let mut builder = SCBConfigBuilder::new();
+        builder.add_profile(&profile);
+        assert!(!format!("{:?}", builder).contains("profile-secret"));
+        #[cfg(feature = "config-file")]
+        {
+            let file = SCBConfigFile::from_toml("[profiles.prod]\nsecret_key = \"file-secret\"\n")
+                .unwrap();
+            assert!(!format!("{:?}", file).contains("file-secret"));
+        }
+    }
+
+    #[test]
+    fn test_client_from_config() {
+        let config = SCBConfigBuilder::new()
+            .add_application_name("app")
+            .add_application_key("key")
+            .add_secret_key("secret")
+            .add_biller_id("123456789012345")
+            .add_ref3_prefix("SHOP")
+            .build()
+            .unwrap();
+        let client = SCBClientAPI::from_config(&config);
+        assert_eq!(client.biller_id(), Some("123456789012345"));
+        assert!(client
+            .ref3_generator()
+            .unwrap()
+            .generate()
+            .starts_with("SHOP"));
+    }
+
+    #[test]
+    fn test_invalid_fields() {
+        let mut builder = SCBConfigBuilder::new();
+        builder
+            .add_application_name("app")
+            .add_application_key("key")
+            .add_secret_key("secret")
+            .add_ref3_prefix("abc");
+        assert!(matches!(
+            builder.build(),
+            Err(SCBConfigError::InvalidField {
+                field: "ref3_prefix",
+                ..
+            })
+        ));
+        builder.add_ref3_prefix("ABC").add_biller_id("12345");
+        assert!(matches!(
+            builder.build(),
+            Err(SCBConfigError::InvalidField {
+                field: "biller_id",
+                ..
+            })
+        ));
+    }
+
+    #[cfg(feature = "config-file")]
+    #[test]
+    fn test_file_profiles() {
+        let toml = SCBConfigFile::from_toml(
+            r#"
+default_profile = "sandbox"
+
+[profiles.sandbox]
+application_name = "sandbox-app"
+
+[profiles.shop-a]
+biller_id = "123456789012345"
+"#,
+        )
+        .unwrap();
+        let yaml = SCBConfigFile::from_yaml(
+            r#"
+default_profile: sandbox
+profiles:
+  sandbox:
+    application_name: sandbox-app
+  shop-a:
+    biller_id: "123456789012345"
+"#,
+        )
+        .unwrap();
+        assert_eq!(toml, yaml);
+        assert_eq!(
+            toml.profile(None).unwrap().application_name.as_deref(),
+            Some("sandbox-app")
+        );
+        assert_eq!(
+            toml.profile(Some("shop-a")).unwrap().biller_id.as_deref(),
+            Some("123456789012345")
+        );
+        assert_eq!(
+            toml.profile(Some("prod")),
+            Err(SCBConfigError::ProfileNotFound("prod".to_string()))
+        );
+    }
+}
@@ -0,0 +1,352 @@
+use std::fmt;
+use std::io::Read;
+#[cfg(feature = "config-file")]
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+#[cfg(feature = "config-file")]
+use std::time::SystemTime;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+#[cfg(feature = "config-file")]
+use crate::frameworks::config::SCBConfigFile;
+use crate::frameworks::config::{SCBConfig, SCBConfigBuilder, SCBConfigError, SCBProfile};
+
+// Application credentials, the key and secret are zeroised on drop.
+// The secret is never serialized, a deserialized client gets it from a credential provider.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Credentials {
+    pub application_name: String,
+    pub application_key: String,
+    #[serde(skip_serializing, default)]
+    pub secret_key: String,
+}
+
+impl Credentials {
+    pub fn new(application_name: &str, application_key: &str, secret_key: &str) -> Credentials {
+        Credentials {
+            application_name: application_name.to_string(),
+            application_key: application_key.to_string(),
+            secret_key: secret_key.to_string(),
+        }
+    }
+}
+
+impl From<&SCBConfig> for Credentials {
+    fn from(config: &SCBConfig) -> Self {
+        Credentials::new(
+            &config.application_name,
+            &config.application_key,
+            &config.secret_key,
+        )
+    }
+}
+
+impl Drop for Credentials {
+    fn drop(&mut self) {
+        self.application_key.zeroize();
+        self.secret_key.zeroize();
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("application_name", &self.application_name)
+            .field("application_key", &self.application_key)
+            .field("secret_key", &"***")
+            .finish()
+    }
+}
+
+// Source of the application credentials. The client asks for them before each token
+// request and drops its cached tokens when they change, so a secret rotated in the SCB portal
+// is picked up without a restart.
+// Called on a blocking thread and abandoned after a timeout, so it may do IO.
+pub trait CredentialProvider: Send + Sync + fmt::Debug {
+    fn credentials(&self) -> Result<Credentials, SCBConfigError>;
+}
+
+#[derive(Debug, Clone)]
+pub struct StaticCredentialProvider {
+    credentials: Credentials,
+}
+
+impl StaticCredentialProvider {
+    pub fn new(credentials: Credentials) -> StaticCredentialProvider {
+        StaticCredentialProvider { credentials }
+    }
+}
+
+impl CredentialProvider for StaticCredentialProvider {
+    fn credentials(&self) -> Result<Credentials, SCBConfigError> {
+        Ok(self.credentials.clone())
+    }
+}
+
+// APP_NAME, APP_KEY and APP_SECRET, read again on every call
+#[derive(Debug, Clone, Default)]
+pub struct EnvCredentialProvider;
+
+impl CredentialProvider for EnvCredentialProvider {
+    fn credentials(&self) -> Result<Credentials, SCBConfigError> {
+        SCBConfigBuilder::new().add_env().build_credentials()
+    }
+}
+
+// A profile of a TOML or YAML config file, read again when the file is modified
+#[cfg(feature = "config-file")]
+#[derive(Debug)]
+pub struct FileCredentialProvider {
+    path: PathBuf,
+    profile: Option<String>,
+    // Credentials read at the modification time of the file
+    cache: Mutex<Option<(SystemTime, Credentials)>>,
+}
+
+#[cfg(feature = "config-file")]
+impl FileCredentialProvider {
+    // The named profile of the file, or its default_profile when no name is given
+    pub fn new(path: impl AsRef<Path>, profile: Option<&str>) -> FileCredentialProvider {
+        FileCredentialProvider {
+            path: path.as_ref().to_path_buf(),
+            profile: profile.map(|p| p.to_string()),
+            cache: Mutex::new(None),
+        }
+    }
+}
+
+#[cfg(feature = "config-file")]
+impl CredentialProvider for FileCredentialProvider {
+    fn credentials(&self) -> Result<Credentials, SCBConfigError> {
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .map_err(|e| SCBConfigError::File {
+                path: self.path.display().to_string(),
+                reason: e.to_string(),
+            })?;
+        let mut cache = self.cache.lock().unwrap();
+        if let Some((read_at, credentials)) = cache.as_ref() {
+            if *read_at == modified {
+                return Ok(credentials.clone());
+            }
+        }
+
+        let profile = SCBConfigFile::load(&self.path)?.profile(self.profile.as_deref())?;
+        let credentials = SCBConfigBuilder::new()
+            .add_profile(&profile)
+            .build_credentials()?;
+        *cache = Some((modified, credentials.clone()));
+        Ok(credentials)
+    }
+}
+
+// Runs a command printing the credentials as JSON, e.g. a secret manager CLI:
+// {"application_name": "...", "application_key": "...", "secret_key": "..."}
+// The output is reused for `ttl` so the command does not run on every token request,
+// a command still running after `timeout` is killed.
+#[derive(Debug)]
+pub struct CommandCredentialProvider {
+    program: String,
+    args: Vec<String>,
+    ttl: Duration,
+    timeout: Duration,
+    cache: Mutex<Option<(Instant, Credentials)>>,
+}
+
+const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+const COMMAND_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+impl CommandCredentialProvider {
+    pub fn new(program: &str, args: &[&str], ttl: Duration) -> CommandCredentialProvider {
+        CommandCredentialProvider {
+            program: program.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            ttl,
+            timeout: DEFAULT_COMMAND_TIMEOUT,
+            cache: Mutex::new(None),
+        }
+    }
+
+    // Time the command may run, 10 seconds by default
+    pub fn set_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn run(&self) -> Result<Credentials, SCBConfigError> {
+        let command_error = |reason: String| SCBConfigError::Command {
+            command: self.program.clone(),
+            reason,
+        };
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| command_error(e.to_string()))?;
+        // Read in threads so a full pipe does not block the command
+        let stdout = read_pipe(child.stdout.take());
+        let stderr = read_pipe(child.stderr.take());
+        let status = wait_timeout(&mut child, self.timeout)
+            .map_err(|e| command_error(e.to_string()))?
+            .ok_or_else(|| command_error(format!("killed after {:?}", self.timeout)))?;
+        let mut stdout = stdout.join().unwrap_or_default();
+        if !status.success() {
+            stdout.zeroize();
+            let stderr = stderr.join().unwrap_or_default();
+            return Err(command_error(format!(
+                "{}: {}",
+                status,
+                String::from_utf8_lossy(&stderr).trim()
+            )));
+        }
+        let profile = serde_json::from_slice::<SCBProfile>(&stdout);
+        stdout.zeroize();
+        let profile = profile.map_err(|e| command_error(e.to_string()))?;
+        SCBConfigBuilder::new()
+            .add_profile(&profile)
+            .build_credentials()
+    }
+}
+
+fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+        output
+    })
+}
+
+// Exit status of the child, or None when it was killed after `timeout`
+fn wait_timeout(
+    child: &mut Child,
+    timeout: Duration,
+) -> std::io::Result<Option<std::process::ExitStatus>> {
+    let started_at = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if started_at.elapsed() >= timeout {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(COMMAND_POLL_INTERVAL);
+    }
+}
+
+impl CredentialProvider for CommandCredentialProvider {
+    fn credentials(&self) -> Result<Credentials, SCBConfigError> {
+        if let Some((fetched_at, credentials)) = self.cache.lock().unwrap().as_ref() {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(credentials.clone());
+            }
+        }
+
+        // Not locked while the command runs
+        let credentials = self.run()?;
+        *self.cache.lock().unwrap() = Some((Instant::now(), credentials.clone()));
+        Ok(credentials)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_provider() {
+        let provider = CommandCredentialProvider::new(
+            "sh",
+            &[
+                "-c",
+                r#"echo '{"application_name": "app", "application_key": "key", "secret_key": "secret"}'"#,
+            ],
+            Duration::from_secs(60),
+        );
+        assert_eq!(
+            provider.credentials().unwrap(),
+            Credentials::new("app", "key", "secret")
+        );
+        assert!(!format!("{:?}", provider.credentials().unwrap()).contains("secret\""));
+
+        let provider = CommandCredentialProvider::new(
+            "sh",
+            &["-c", r#"echo '{"application_name": "app"}'"#],
+            Duration::ZERO,
+        );
+        assert_eq!(
+            provider.credentials(),
+            Err(SCBConfigError::MissingFields(vec![
+                "application_key (APP_KEY)",
+                "secret_key (APP_SECRET)"
+            ]))
+        );
+
+        let provider = CommandCredentialProvider::new("false", &[], Duration::ZERO);
+        assert!(matches!(
+            provider.credentials(),
+            Err(SCBConfigError::Command { .. })
+        ));
+    }
+
+    #[test]
+    fn test_command_provider_timeout() {
+        let mut provider = CommandCredentialProvider::new("sleep", &["5"], Duration::ZERO);
+        provider.set_timeout(Duration::from_millis(100));
+        let started_at = Instant::now();
+        assert!(matches!(
+            provider.credentials(),
+            Err(SCBConfigError::Command { reason, .. }) if reason.starts_with("killed")
+        ));
+        assert!(started_at.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_secret_not_serialized() {
+        let json = serde_json::to_string(&Credentials::new("app", "key", "secret")).unwrap();
+        assert!(!json.contains("secret\""));
+        let credentials: Credentials = serde_json::from_str(&json).unwrap();
+        assert_eq!(credentials, Credentials::new("app", "key", ""));
+    }
+
+    #[cfg(feature = "config-file")]
+    #[test]
+    fn test_file_provider_reloads() {
+        let path =
+            std::env::temp_dir().join(format!("scb-credentials-{}.toml", std::process::id()));
+        let write = |secret: &str, modified: SystemTime| {
+            std::fs::write(
+                &path,
+                format!(
+                    "[profiles.prod]\napplication_name = \"app\"\napplication_key = \"key\"\nsecret_key = \"{}\"\n",
+                    secret
+                ),
+            )
+            .unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        };
+        let provider = FileCredentialProvider::new(&path, Some("prod"));
+
+        let now = SystemTime::now();
+        write("old", now - Duration::from_secs(60));
+        assert_eq!(provider.credentials().unwrap().secret_key, "old");
+        write("new", now);
+        assert_eq!(provider.credentials().unwrap().secret_key, "new");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
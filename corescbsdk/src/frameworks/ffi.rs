@@ -0,0 +1,402 @@
+// C ABI over the blocking client, see include/corescbsdk.h.
+//
+// Requests and responses are JSON strings with the field names of the SCB API.
+// Strings returned by the library are owned by the caller and released with scb_string_free.
+// Functions return SCBErrorCode, the message of the last error on the calling thread
+// is available from scb_last_error_message.
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::sync::Arc;
+
+use serde::Serialize;
+use validator::Validate;
+
+use crate::entities::bank_code::BankCode;
+use crate::entities::bill_pay::BillPaymentInquiryRequest;
+use crate::entities::qr_code::QRCodeRequest;
+use crate::errors::scb_error::SCBAPIError;
+use crate::frameworks::apis::blocking::SCBClientAPI;
+use crate::frameworks::stores::sqlite::SqliteSlipRegistry;
+
+/// Result of the library calls, details in scb_last_error_message
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SCBErrorCode {
+    Ok = 0,
+    /// Null pointer, invalid UTF-8, invalid JSON or a request failing validation
+    InvalidArgument = 1,
+    HttpRequest = 2,
+    /// Error status returned by SCB
+    SCB = 3,
+    Authorization = 4,
+    DuplicateSlip = 5,
+    Storage = 6,
+    RateLimited = 7,
+    CircuitOpen = 8,
+    /// Bug in the library, the client handle should not be used anymore
+    Panic = 9,
+    /// 5xx status from the SCB gateway
+    Server = 10,
+    /// Response which is not an SCB response
+    InvalidResponse = 11,
+}
+
+impl From<&SCBAPIError> for SCBErrorCode {
+    fn from(error: &SCBAPIError) -> Self {
+        match error {
+            SCBAPIError::HttpRequestError(_) => SCBErrorCode::HttpRequest,
+            SCBAPIError::SCBError(_) => SCBErrorCode::SCB,
+            SCBAPIError::AuthorizationError(_) => SCBErrorCode::Authorization,
+            SCBAPIError::DuplicateSlip { .. } => SCBErrorCode::DuplicateSlip,
+            SCBAPIError::StorageError(_) => SCBErrorCode::Storage,
+            SCBAPIError::RateLimited { .. } => SCBErrorCode::RateLimited,
+            SCBAPIError::CircuitOpen { .. } => SCBErrorCode::CircuitOpen,
+            SCBAPIError::ServerError { .. } => SCBErrorCode::Server,
+            SCBAPIError::InvalidResponse(_) => SCBErrorCode::InvalidResponse,
+        }
+    }
+}
+
+/// Opaque client handle, created with scb_client_new and released with scb_client_free.
+/// A handle must not be used from several threads at the same time.
+pub struct SCBClientHandle {
+    client: SCBClientAPI,
+}
+
+struct FfiError {
+    code: SCBErrorCode,
+    message: String,
+}
+
+impl FfiError {
+    fn invalid_argument(message: String) -> Self {
+        FfiError {
+            code: SCBErrorCode::InvalidArgument,
+            message,
+        }
+    }
+}
+
+impl From<SCBAPIError> for FfiError {
+    fn from(error: SCBAPIError) -> Self {
+        FfiError {
+            code: SCBErrorCode::from(&error),
+            message: error.to_string(),
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: &str) {
+    // Interior NUL bytes cannot be represented in a C string
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+// Run `f` catching panics, which must not unwind into C, and record its error
+fn ffi_call<F>(f: F) -> SCBErrorCode
+where
+    F: FnOnce() -> Result<(), FfiError>,
+{
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => SCBErrorCode::Ok,
+        Ok(Err(e)) => {
+            set_last_error(&e.message);
+            e.code
+        }
+        Err(_) => {
+            set_last_error("panic in corescbsdk");
+            SCBErrorCode::Panic
+        }
+    }
+}
+
+unsafe fn read_str<'a>(value: *const c_char, name: &str) -> Result<&'a str, FfiError> {
+    if value.is_null() {
+        return Err(FfiError::invalid_argument(format!("{} is null", name)));
+    }
+    CStr::from_ptr(value)
+        .to_str()
+        .map_err(|_| FfiError::invalid_argument(format!("{} is not valid UTF-8", name)))
+}
+
+unsafe fn client_mut<'a>(client: *mut SCBClientHandle) -> Result<&'a mut SCBClientAPI, FfiError> {
+    client
+        .as_mut()
+        .map(|handle| &mut handle.client)
+        .ok_or_else(|| FfiError::invalid_argument("client is null".to_string()))
+}
+
+unsafe fn write_json<T: Serialize>(value: &T, out_json: *mut *mut c_char) -> Result<(), FfiError> {
+    if out_json.is_null() {
+        return Err(FfiError::invalid_argument("out_json is null".to_string()));
+    }
+    let json = serde_json::to_string(value)
+        .map_err(|e| FfiError::invalid_argument(format!("failed to serialize response: {}", e)))?;
+    // JSON escapes control characters so it has no NUL bytes
+    *out_json = CString::new(json).unwrap().into_raw();
+    Ok(())
+}
+
+/// Create a client with the credentials of an SCB application.
+/// Returns null on invalid arguments, see scb_last_error_message.
+///
+/// # Safety
+/// The arguments must be null or valid NUL terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn scb_client_new(
+    application_name: *const c_char,
+    application_key: *const c_char,
+    secret_key: *const c_char,
+) -> *mut SCBClientHandle {
+    let mut handle = ptr::null_mut();
+    ffi_call(|| {
+        let application_name = read_str(application_name, "application_name")?.to_string();
+        let application_key = read_str(application_key, "application_key")?.to_string();
+        let secret_key = read_str(secret_key, "secret_key")?.to_string();
+        let client = SCBClientAPI::new(&application_name, &application_key, &secret_key);
+        handle = Box::into_raw(Box::new(SCBClientHandle { client }));
+        Ok(())
+    });
+    handle
+}
+
+/// Release a client created with scb_client_new. Null is ignored.
+///
+/// # Safety
+/// `client` must be null or a handle from scb_client_new which was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn scb_client_free(client: *mut SCBClientHandle) {
+    if !client.is_null() {
+        drop(Box::from_raw(client));
+    }
+}
+
+/// Claim the slips verified with an order id in the SQLite database at `path`,
+/// created when missing. Several processes may share the database.
+///
+/// # Safety
+/// `client` must be a live handle and `path` a valid NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn scb_client_set_slip_registry_sqlite(
+    client: *mut SCBClientHandle,
+    path: *const c_char,
+) -> SCBErrorCode {
+    ffi_call(|| {
+        let client = client_mut(client)?;
+        let registry =
+            SqliteSlipRegistry::open(read_str(path, "path")?).map_err(SCBAPIError::from)?;
+        client.set_slip_registry(Arc::new(registry));
+        Ok(())
+    })
+}
+
+/// Create a QR code. `request_json` is the body of the QR code create API,
+/// e.g. {"qrType":"PP","amount":"100.00","ppType":"BILLERID","ppId":"...","ref1":"..."}.
+/// On success `*out_json` is set to the response data.
+///
+/// # Safety
+/// `client` must be a live handle, `request_json` a valid NUL terminated string
+/// and `out_json` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn scb_qr_code_create(
+    client: *mut SCBClientHandle,
+    request_json: *const c_char,
+    out_json: *mut *mut c_char,
+) -> SCBErrorCode {
+    ffi_call(|| {
+        let client = client_mut(client)?;
+        let request: QRCodeRequest = serde_json::from_str(read_str(request_json, "request_json")?)
+            .map_err(|e| FfiError::invalid_argument(format!("invalid request_json: {}", e)))?;
+        request
+            .validate()
+            .map_err(|e| FfiError::invalid_argument(format!("invalid QR code request: {}", e)))?;
+        let response = client.qr_code_create(&request)?;
+        write_json(&response, out_json)
+    })
+}
+
+/// Verify a slip by its transaction reference and the 3 digit code of the sending bank, e.g. "014".
+/// When `order_id` is not null the slip is claimed for the order in the registry set with
+/// scb_client_set_slip_registry_sqlite, without a registry the call fails with SCB_ERROR_CODE_STORAGE.
+/// On success `*out_json` is set to the slip.
+///
+/// # Safety
+/// `client` must be a live handle, `trans_ref` and `sending_bank` valid NUL terminated strings,
+/// `order_id` null or a valid NUL terminated string and `out_json` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn scb_slip_verify(
+    client: *mut SCBClientHandle,
+    trans_ref: *const c_char,
+    sending_bank: *const c_char,
+    order_id: *const c_char,
+    out_json: *mut *mut c_char,
+) -> SCBErrorCode {
+    ffi_call(|| {
+        let client = client_mut(client)?;
+        let trans_ref = read_str(trans_ref, "trans_ref")?.to_string();
+        let sending_bank = BankCode::from_code(read_str(sending_bank, "sending_bank")?);
+        let slip = if order_id.is_null() {
+            client.get_slip_verification_qr30(&trans_ref, &sending_bank)?
+        } else {
+            let order_id = read_str(order_id, "order_id")?;
+            client.get_slip_verification_for_order(&trans_ref, &sending_bank, order_id)?
+        };
+        write_json(&slip, out_json)
+    })
+}
+
+/// Query bill payment transactions. `request_json` holds the query parameters,
+/// e.g. {"eventCode":"00300100","transactionDate":"2024-01-31","billerId":"...","reference1":"..."}.
+/// On success `*out_json` is set to a JSON array of transactions.
+///
+/// # Safety
+/// `client` must be a live handle, `request_json` a valid NUL terminated string
+/// and `out_json` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn scb_bill_payment_inquiry(
+    client: *mut SCBClientHandle,
+    request_json: *const c_char,
+    out_json: *mut *mut c_char,
+) -> SCBErrorCode {
+    ffi_call(|| {
+        let client = client_mut(client)?;
+        let request: BillPaymentInquiryRequest =
+            serde_json::from_str(read_str(request_json, "request_json")?)
+                .map_err(|e| FfiError::invalid_argument(format!("invalid request_json: {}", e)))?;
+        request
+            .validate()
+            .map_err(|e| FfiError::invalid_argument(format!("invalid inquiry request: {}", e)))?;
+        let transactions = client.query_bill_payment_transaction(&request)?;
+        write_json(&transactions, out_json)
+    })
+}
+
+/// Copy of the message of the last failed call on this thread, or null.
+/// Release it with scb_string_free.
+#[no_mangle]
+pub extern "C" fn scb_last_error_message() -> *mut c_char {
+    LAST_ERROR.with(|e| {
+        e.borrow()
+            .as_ref()
+            .map_or(ptr::null_mut(), |message| message.clone().into_raw())
+    })
+}
+
+/// Release a string returned by the library. Null is ignored.
+///
+/// # Safety
+/// `value` must be null or a string returned by the library which was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn scb_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_error() -> String {
+        let message = scb_last_error_message();
+        let text = unsafe { CStr::from_ptr(message) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        unsafe { scb_string_free(message) };
+        text
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        let name = CString::new("app").unwrap();
+        unsafe {
+            assert!(scb_client_new(name.as_ptr(), ptr::null(), name.as_ptr()).is_null());
+            assert_eq!(last_error(), "application_key is null");
+
+            let client = scb_client_new(name.as_ptr(), name.as_ptr(), name.as_ptr());
+            assert!(!client.is_null());
+            let mut out_json = ptr::null_mut();
+            let request = CString::new("{\"qrType\":").unwrap();
+            assert_eq!(
+                scb_qr_code_create(client, request.as_ptr(), &mut out_json),
+                SCBErrorCode::InvalidArgument
+            );
+            assert!(last_error().starts_with("invalid request_json"));
+
+            let request = CString::new("{\"qrType\":\"PP\",\"amount\":\"abc\"}").unwrap();
+            assert_eq!(
+                scb_qr_code_create(client, request.as_ptr(), &mut out_json),
+                SCBErrorCode::InvalidArgument
+            );
+            assert!(last_error().starts_with("invalid QR code request"));
+            assert!(out_json.is_null());
+            scb_client_free(client);
+        }
+    }
+
+    #[test]
+    fn test_slip_registry() {
+        let name = CString::new("app").unwrap();
+        let trans_ref = CString::new("REF").unwrap();
+        let bank = CString::new("014").unwrap();
+        let order_id = CString::new("ORDER1").unwrap();
+        let path = CString::new(":memory:").unwrap();
+        unsafe {
+            let client = scb_client_new(name.as_ptr(), name.as_ptr(), name.as_ptr());
+            let mut out_json = ptr::null_mut();
+            assert_eq!(
+                scb_slip_verify(
+                    client,
+                    trans_ref.as_ptr(),
+                    bank.as_ptr(),
+                    order_id.as_ptr(),
+                    &mut out_json
+                ),
+                SCBErrorCode::Storage
+            );
+            assert!(last_error().contains("no slip registry"));
+            assert_eq!(
+                scb_client_set_slip_registry_sqlite(client, path.as_ptr()),
+                SCBErrorCode::Ok
+            );
+            assert_eq!(
+                scb_client_set_slip_registry_sqlite(client, ptr::null()),
+                SCBErrorCode::InvalidArgument
+            );
+            scb_client_free(client);
+        }
+    }
+
+    #[test]
+    fn test_header_is_current() {
+        assert!(
+            include_str!(concat!(env!("OUT_DIR"), "/corescbsdk.h"))
+                == include_str!("../../include/corescbsdk.h"),
+            "include/corescbsdk.h is outdated, build with CORESCBSDK_UPDATE_HEADER=1"
+        );
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let error = SCBAPIError::DuplicateSlip {
+            trans_ref: "REF".to_string(),
+            order_id: "ORDER1".to_string(),
+        };
+        assert_eq!(SCBErrorCode::from(&error), SCBErrorCode::DuplicateSlip);
+        assert_eq!(
+            ffi_call(|| Err(FfiError::from(error))),
+            SCBErrorCode::DuplicateSlip
+        );
+        assert_eq!(
+            last_error(),
+            "Duplicate Slip: REF was already used for order ORDER1"
+        );
+    }
+}
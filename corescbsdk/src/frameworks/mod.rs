@@ -1 +1,8 @@
 pub mod apis;
+pub mod config;
+pub mod credentials;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "slip-image")]
+pub mod slip_image;
+pub mod stores;
@@ -0,0 +1,279 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::io::Cursor;
+
+use image::imageops;
+use image::{GrayImage, ImageReader, Limits};
+use tracing::debug;
+
+use crate::entities::slip_qr::SlipQRCode;
+
+// Photos from phone cameras are scaled down to this size before a second attempt,
+// QR detection works better when the finder patterns are not too large.
+const MAX_DIMENSION: u32 = 1600;
+// Images above these limits are rejected before decoding
+const MAX_IMAGE_DIMENSION: u32 = 12_000;
+const MAX_IMAGE_ALLOC: u64 = 256 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum SlipImageError {
+    // Bytes are not a PNG or JPEG image
+    InvalidImage(image::ImageError),
+    // No QR in the image is a slip verification QR
+    NoSlipQRFound {
+        // Number of QR codes decoded which were not slip QR
+        other_qr_codes: usize,
+    },
+}
+
+impl fmt::Display for SlipImageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SlipImageError::InvalidImage(e) => write!(f, "invalid slip image: {}", e),
+            SlipImageError::NoSlipQRFound { other_qr_codes: 0 } => {
+                write!(f, "no slip QR found in image")
+            }
+            SlipImageError::NoSlipQRFound { other_qr_codes } => write!(
+                f,
+                "no slip QR found in image, {} other QR code(s) ignored",
+                other_qr_codes
+            ),
+        }
+    }
+}
+
+impl From<image::ImageError> for SlipImageError {
+    fn from(error: image::ImageError) -> Self {
+        SlipImageError::InvalidImage(error)
+    }
+}
+
+// Locate and decode the slip verification QR in a PNG or JPEG image of a bank slip.
+// Screenshots usually decode on the first attempt, camera photos fall back to
+// scaling, contrast stretching and binarization. Rotated QR codes are found as is.
+pub fn decode_slip_qr(image_bytes: &[u8]) -> Result<SlipQRCode, SlipImageError> {
+    let gray = load_gray(image_bytes)?;
+    let mut other_qr_codes = 0;
+
+    // Each candidate is only prepared once the previous one failed
+    let mut candidate = Some((Preprocess::Original, gray));
+    while let Some((step, image)) = candidate {
+        let payloads = decode_qr_payloads(&image);
+        debug!("Attempt {:?} decoded {} QR code(s)", step, payloads.len());
+
+        let mut others = 0;
+        for payload in payloads {
+            match SlipQRCode::parse(&payload) {
+                Ok(slip_qr) => return Ok(slip_qr),
+                Err(e) => {
+                    debug!("Ignore QR code: {}", e);
+                    others += 1;
+                }
+            }
+        }
+        other_qr_codes = other_qr_codes.max(others);
+        candidate = step.next(&image);
+    }
+    Err(SlipImageError::NoSlipQRFound { other_qr_codes })
+}
+
+fn load_gray(image_bytes: &[u8]) -> Result<GrayImage, SlipImageError> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+    limits.max_alloc = Some(MAX_IMAGE_ALLOC);
+
+    let mut reader = ImageReader::new(Cursor::new(image_bytes))
+        .with_guessed_format()
+        .map_err(image::ImageError::IoError)?;
+    reader.limits(limits);
+    Ok(reader.decode()?.to_luma8())
+}
+
+// Preprocessing of a decode attempt, each step works on the image of the previous one
+#[derive(Debug, Clone, Copy)]
+enum Preprocess {
+    Original,
+    Scaled,
+    Stretched,
+    Binarized,
+}
+
+impl Preprocess {
+    fn next(self, image: &GrayImage) -> Option<(Preprocess, GrayImage)> {
+        match self {
+            // Large photos are scaled down before the more expensive steps
+            Preprocess::Original => match scale_down(image) {
+                Some(scaled) => Some((Preprocess::Scaled, scaled)),
+                None => Some((Preprocess::Stretched, stretch_contrast(image))),
+            },
+            Preprocess::Scaled => Some((Preprocess::Stretched, stretch_contrast(image))),
+            Preprocess::Stretched => Some((Preprocess::Binarized, binarize(image))),
+            Preprocess::Binarized => None,
+        }
+    }
+}
+
+fn decode_qr_payloads(gray: &GrayImage) -> Vec<String> {
+    let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(
+        gray.width() as usize,
+        gray.height() as usize,
+        |x, y| gray.get_pixel(x as u32, y as u32).0[0],
+    );
+    prepared
+        .detect_grids()
+        .into_iter()
+        .filter_map(|grid| grid.decode().ok())
+        .map(|(_, payload)| payload)
+        .collect()
+}
+
+// Map the darkest pixel to black and the brightest to white
+fn stretch_contrast(gray: &GrayImage) -> GrayImage {
+    let (min, max) = gray.pixels().fold((u8::MAX, u8::MIN), |(min, max), p| {
+        (min.min(p.0[0]), max.max(p.0[0]))
+    });
+    if max <= min {
+        return gray.clone();
+    }
+    let range = (max - min) as u32;
+    let mut out = gray.clone();
+    for p in out.pixels_mut() {
+        p.0[0] = ((p.0[0] - min) as u32 * 255 / range) as u8;
+    }
+    out
+}
+
+// Black and white image using Otsu's threshold
+fn binarize(gray: &GrayImage) -> GrayImage {
+    let mut histogram = [0u64; 256];
+    for p in gray.pixels() {
+        histogram[p.0[0] as usize] += 1;
+    }
+    let total: u64 = histogram.iter().sum();
+    let sum_all: u64 = histogram
+        .iter()
+        .enumerate()
+        .map(|(i, c)| i as u64 * c)
+        .sum();
+
+    let (mut sum_background, mut weight_background) = (0u64, 0u64);
+    let (mut best_threshold, mut best_variance) = (127u8, 0f64);
+    for (i, count) in histogram.iter().enumerate() {
+        weight_background += count;
+        if weight_background == 0 {
+            continue;
+        }
+        let weight_foreground = total - weight_background;
+        if weight_foreground == 0 {
+            break;
+        }
+        sum_background += i as u64 * count;
+        let mean_background = sum_background as f64 / weight_background as f64;
+        let mean_foreground = (sum_all - sum_background) as f64 / weight_foreground as f64;
+        let variance = weight_background as f64
+            * weight_foreground as f64
+            * (mean_background - mean_foreground).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best_threshold = i as u8;
+        }
+    }
+
+    let mut out = gray.clone();
+    for p in out.pixels_mut() {
+        p.0[0] = if p.0[0] > best_threshold { 255 } else { 0 };
+    }
+    out
+}
+
+fn scale_down(gray: &GrayImage) -> Option<GrayImage> {
+    let longest = gray.width().max(gray.height());
+    if longest <= MAX_DIMENSION {
+        return None;
+    }
+    let ratio = MAX_DIMENSION as f64 / longest as f64;
+    Some(imageops::resize(
+        gray,
+        (gray.width() as f64 * ratio) as u32,
+        (gray.height() as f64 * ratio) as u32,
+        imageops::FilterType::Triangle,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{ImageFormat, Luma};
+    use qrcode::{Color, QrCode};
+
+    use super::*;
+    use crate::entities::emv::crc16_ccitt;
+
+    fn with_crc(body: &str) -> String {
+        format!("{}{:04X}", body, crc16_ccitt(body.as_bytes()))
+    }
+
+    // Render a QR with a quiet zone, `dark` and `light` control the contrast
+    fn render(payloads: &[&str], dark: u8, light: u8) -> GrayImage {
+        let scale = 6;
+        let codes: Vec<QrCode> = payloads.iter().map(|p| QrCode::new(p).unwrap()).collect();
+        let size = codes.iter().map(|c| c.width() as u32 + 8).max().unwrap() * scale;
+        let mut img = GrayImage::from_pixel(size * codes.len() as u32, size, Luma([light]));
+        for (i, code) in codes.iter().enumerate() {
+            let width = code.width();
+            for (n, color) in code.to_colors().iter().enumerate() {
+                if *color == Color::Dark {
+                    let (x, y) = ((n % width) as u32 + 4, (n / width) as u32 + 4);
+                    for dx in 0..scale {
+                        for dy in 0..scale {
+                            img.put_pixel(
+                                i as u32 * size + x * scale + dx,
+                                y * scale + dy,
+                                Luma([dark]),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        img
+    }
+
+    fn encode_png(img: &GrayImage) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        img.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_decode_rotated_low_contrast_slip() {
+        let slip = with_crc("004600060000010103014022520230101121000000000000005102TH9104");
+        let img = imageops::rotate90(&render(&["https://example.com", &slip], 110, 150));
+        let slip_qr = decode_slip_qr(&encode_png(&img)).unwrap();
+        assert_eq!(slip_qr.sending_bank.code(), "014");
+        assert_eq!(slip_qr.trans_ref, "2023010112100000000000000");
+    }
+
+    #[test]
+    fn test_no_slip_qr() {
+        let img = render(&["https://example.com"], 0, 255);
+        assert!(matches!(
+            decode_slip_qr(&encode_png(&img)),
+            Err(SlipImageError::NoSlipQRFound { other_qr_codes: 1 })
+        ));
+        assert!(matches!(
+            decode_slip_qr(b"not an image"),
+            Err(SlipImageError::InvalidImage(_))
+        ));
+    }
+
+    #[test]
+    fn test_image_over_limits() {
+        let img = GrayImage::from_pixel(MAX_IMAGE_DIMENSION + 1, 1, Luma([255]));
+        assert!(matches!(
+            decode_slip_qr(&encode_png(&img)),
+            Err(SlipImageError::InvalidImage(image::ImageError::Limits(_)))
+        ));
+    }
+}
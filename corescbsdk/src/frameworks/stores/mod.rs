@@ -0,0 +1,4 @@
+pub mod payment_store;
+pub mod slip_registry;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
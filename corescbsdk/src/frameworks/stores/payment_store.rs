@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Formatter;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum PaymentStatus {
+    // QR created, not paid yet
+    Created,
+    Paid,
+    Cancelled,
+    Expired,
+    // qr_code_create failed, the error is kept on the record
+    Failed,
+    // Any status not known by this SDK, e.g. written by a newer version
+    Unknown(String),
+}
+
+impl From<String> for PaymentStatus {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "CREATED" => PaymentStatus::Created,
+            "PAID" => PaymentStatus::Paid,
+            "CANCELLED" => PaymentStatus::Cancelled,
+            "EXPIRED" => PaymentStatus::Expired,
+            "FAILED" => PaymentStatus::Failed,
+            _ => PaymentStatus::Unknown(value),
+        }
+    }
+}
+
+impl From<PaymentStatus> for String {
+    fn from(value: PaymentStatus) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for PaymentStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PaymentStatus::Created => write!(f, "CREATED"),
+            PaymentStatus::Paid => write!(f, "PAID"),
+            PaymentStatus::Cancelled => write!(f, "CANCELLED"),
+            PaymentStatus::Expired => write!(f, "EXPIRED"),
+            PaymentStatus::Failed => write!(f, "FAILED"),
+            PaymentStatus::Unknown(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+// One QR created through qr_code_create
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentRecord {
+    // requestUId header sent with qr_code_create, unique per record
+    pub request_uid: String,
+    pub qr_type: String,
+    pub amount: String,
+    // ppId of the request, the biller id of a QR 30
+    pub pp_id: Option<String>,
+    pub ref1: Option<String>,
+    pub ref2: Option<String>,
+    pub ref3: Option<String>,
+    pub invoice: Option<String>,
+    // qrCodeId of the response, QR CS / PPCS only
+    pub qr_code_id: Option<String>,
+    // QRCodeRequest as sent, JSON
+    pub request: String,
+    // QRCodeResponse as received, JSON
+    pub response: Option<String>,
+    // Error of a failed qr_code_create
+    pub error: Option<String>,
+    pub status: PaymentStatus,
+    // Unix timestamp of qr_code_create
+    pub created_at: i64,
+    // Unix timestamp of the last status change
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaymentQuery {
+    RequestUId(String),
+    Ref1(String),
+    Ref2(String),
+    Ref3(String),
+    Invoice(String),
+    QRCodeId(String),
+    // QR 30 of a biller id with this reference 1
+    Biller { pp_id: String, ref1: String },
+}
+
+impl PaymentQuery {
+    pub fn matches(&self, record: &PaymentRecord) -> bool {
+        let (field, value) = match self {
+            PaymentQuery::Biller { pp_id, ref1 } => {
+                return record.pp_id.as_ref() == Some(pp_id) && record.ref1.as_ref() == Some(ref1);
+            }
+            PaymentQuery::RequestUId(v) => (Some(&record.request_uid), v),
+            PaymentQuery::Ref1(v) => (record.ref1.as_ref(), v),
+            PaymentQuery::Ref2(v) => (record.ref2.as_ref(), v),
+            PaymentQuery::Ref3(v) => (record.ref3.as_ref(), v),
+            PaymentQuery::Invoice(v) => (record.invoice.as_ref(), v),
+            PaymentQuery::QRCodeId(v) => (record.qr_code_id.as_ref(), v),
+        };
+        field == Some(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaymentStoreError {
+    // No record with this requestUId
+    NotFound(String),
+    // The underlying storage failed
+    Storage(String),
+}
+
+impl fmt::Display for PaymentStoreError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PaymentStoreError::NotFound(request_uid) => {
+                write!(f, "payment {} not found", request_uid)
+            }
+            PaymentStoreError::Storage(e) => write!(f, "payment store storage error: {}", e),
+        }
+    }
+}
+
+// Keeps every QR created by the client with its request, response and final status
+pub trait PaymentStore: Send + Sync + fmt::Debug {
+    // Insert the record, or replace the record with the same request_uid
+    fn save(&self, record: &PaymentRecord) -> Result<(), PaymentStoreError>;
+
+    // Records matching the query, oldest first
+    fn find(&self, query: &PaymentQuery) -> Result<Vec<PaymentRecord>, PaymentStoreError>;
+
+    fn update_status(
+        &self,
+        request_uid: &str,
+        status: PaymentStatus,
+    ) -> Result<PaymentRecord, PaymentStoreError>;
+}
+
+// Store kept in memory, suitable for a single process and for tests
+#[derive(Debug, Default)]
+pub struct InMemoryPaymentStore {
+    records: Mutex<HashMap<String, PaymentRecord>>,
+}
+
+impl InMemoryPaymentStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PaymentStore for InMemoryPaymentStore {
+    fn save(&self, record: &PaymentRecord) -> Result<(), PaymentStoreError> {
+        let mut records = self
+            .records
+            .lock()
+            .map_err(|e| PaymentStoreError::Storage(e.to_string()))?;
+        records.insert(record.request_uid.clone(), record.clone());
+        Ok(())
+    }
+
+    fn find(&self, query: &PaymentQuery) -> Result<Vec<PaymentRecord>, PaymentStoreError> {
+        let records = self
+            .records
+            .lock()
+            .map_err(|e| PaymentStoreError::Storage(e.to_string()))?;
+        let mut found: Vec<PaymentRecord> = records
+            .values()
+            .filter(|r| query.matches(r))
+            .cloned()
+            .collect();
+        found.sort_by(|a, b| (a.created_at, &a.request_uid).cmp(&(b.created_at, &b.request_uid)));
+        Ok(found)
+    }
+
+    fn update_status(
+        &self,
+        request_uid: &str,
+        status: PaymentStatus,
+    ) -> Result<PaymentRecord, PaymentStoreError> {
+        let mut records = self
+            .records
+            .lock()
+            .map_err(|e| PaymentStoreError::Storage(e.to_string()))?;
+        let record = records
+            .get_mut(request_uid)
+            .ok_or_else(|| PaymentStoreError::NotFound(request_uid.to_string()))?;
+        record.status = status;
+        record.updated_at = chrono::Utc::now().timestamp();
+        Ok(record.clone())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn record(request_uid: &str, ref1: &str, created_at: i64) -> PaymentRecord {
+        PaymentRecord {
+            request_uid: request_uid.to_string(),
+            qr_type: "PP".to_string(),
+            amount: "100.00".to_string(),
+            pp_id: Some("123456789012345".to_string()),
+            ref1: Some(ref1.to_string()),
+            ref2: None,
+            ref3: Some("SCB1".to_string()),
+            invoice: None,
+            qr_code_id: None,
+            request: "{}".to_string(),
+            response: Some("{}".to_string()),
+            error: None,
+            status: PaymentStatus::Created,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    #[test]
+    fn test_in_memory_store() {
+        let store = InMemoryPaymentStore::new();
+        store.save(&record("UID2", "ORDER1", 2)).unwrap();
+        store.save(&record("UID1", "ORDER1", 1)).unwrap();
+        store.save(&record("UID3", "ORDER2", 3)).unwrap();
+
+        let found = store
+            .find(&PaymentQuery::Ref1("ORDER1".to_string()))
+            .unwrap();
+        let uids: Vec<&str> = found.iter().map(|r| r.request_uid.as_str()).collect();
+        assert_eq!(uids, vec!["UID1", "UID2"]);
+
+        let biller = PaymentQuery::Biller {
+            pp_id: "123456789012345".to_string(),
+            ref1: "ORDER2".to_string(),
+        };
+        assert_eq!(store.find(&biller).unwrap().len(), 1);
+
+        let updated = store.update_status("UID3", PaymentStatus::Paid).unwrap();
+        assert_eq!(updated.status, PaymentStatus::Paid);
+        assert!(matches!(
+            store.update_status("UID9", PaymentStatus::Paid),
+            Err(PaymentStoreError::NotFound(_))
+        ));
+    }
+}
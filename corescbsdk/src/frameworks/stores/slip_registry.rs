@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Formatter;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::scb_error::SCBAPIError;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlipClaim {
+    // Transaction Slip ID
+    pub trans_ref: String,
+    // Sender Bank Code
+    pub sending_bank: String,
+    // Order the slip was accepted for
+    pub order_id: String,
+    // Unix timestamp of the claim
+    pub claimed_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlipRegistryError {
+    // The slip was already accepted for another order
+    AlreadyClaimed(SlipClaim),
+    // The underlying storage failed
+    Storage(String),
+}
+
+impl fmt::Display for SlipRegistryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SlipRegistryError::AlreadyClaimed(claim) => write!(
+                f,
+                "slip {} from bank {} was already used for order {}",
+                claim.trans_ref, claim.sending_bank, claim.order_id
+            ),
+            SlipRegistryError::Storage(e) => write!(f, "slip registry storage error: {}", e),
+        }
+    }
+}
+
+impl From<SlipRegistryError> for SCBAPIError {
+    fn from(error: SlipRegistryError) -> Self {
+        match error {
+            SlipRegistryError::AlreadyClaimed(claim) => SCBAPIError::DuplicateSlip {
+                trans_ref: claim.trans_ref,
+                order_id: claim.order_id,
+            },
+            SlipRegistryError::Storage(e) => SCBAPIError::StorageError(e),
+        }
+    }
+}
+
+// Remembers which slips have been accepted so the same slip cannot pay for two orders.
+// A slip is identified by trans_ref + sending_bank.
+pub trait SlipRegistry: Send + Sync + fmt::Debug {
+    // Atomically claim the slip for `order_id`.
+    // Claiming again for the same order succeeds so retries are safe.
+    fn claim(
+        &self,
+        trans_ref: &str,
+        sending_bank: &str,
+        order_id: &str,
+    ) -> Result<SlipClaim, SlipRegistryError>;
+
+    fn find(
+        &self,
+        trans_ref: &str,
+        sending_bank: &str,
+    ) -> Result<Option<SlipClaim>, SlipRegistryError>;
+
+    // Release a claim, e.g. when the order is refunded. Returns false if the slip was not claimed.
+    fn release(&self, trans_ref: &str, sending_bank: &str) -> Result<bool, SlipRegistryError>;
+}
+
+// Registry kept in memory, suitable for a single process and for tests
+#[derive(Debug, Default)]
+pub struct InMemorySlipRegistry {
+    claims: Mutex<HashMap<(String, String), SlipClaim>>,
+}
+
+impl InMemorySlipRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SlipRegistry for InMemorySlipRegistry {
+    fn claim(
+        &self,
+        trans_ref: &str,
+        sending_bank: &str,
+        order_id: &str,
+    ) -> Result<SlipClaim, SlipRegistryError> {
+        let mut claims = self
+            .claims
+            .lock()
+            .map_err(|e| SlipRegistryError::Storage(e.to_string()))?;
+        let claim = claims
+            .entry((trans_ref.to_string(), sending_bank.to_string()))
+            .or_insert_with(|| SlipClaim {
+                trans_ref: trans_ref.to_string(),
+                sending_bank: sending_bank.to_string(),
+                order_id: order_id.to_string(),
+                claimed_at: chrono::Utc::now().timestamp(),
+            });
+        if claim.order_id != order_id {
+            return Err(SlipRegistryError::AlreadyClaimed(claim.clone()));
+        }
+        Ok(claim.clone())
+    }
+
+    fn find(
+        &self,
+        trans_ref: &str,
+        sending_bank: &str,
+    ) -> Result<Option<SlipClaim>, SlipRegistryError> {
+        let claims = self
+            .claims
+            .lock()
+            .map_err(|e| SlipRegistryError::Storage(e.to_string()))?;
+        Ok(claims
+            .get(&(trans_ref.to_string(), sending_bank.to_string()))
+            .cloned())
+    }
+
+    fn release(&self, trans_ref: &str, sending_bank: &str) -> Result<bool, SlipRegistryError> {
+        let mut claims = self
+            .claims
+            .lock()
+            .map_err(|e| SlipRegistryError::Storage(e.to_string()))?;
+        Ok(claims
+            .remove(&(trans_ref.to_string(), sending_bank.to_string()))
+            .is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory_claim() {
+        let registry = InMemorySlipRegistry::new();
+        let claim = registry.claim("TREF1", "014", "ORDER1").unwrap();
+        assert_eq!(claim.order_id, "ORDER1");
+        // Retry of the same order
+        assert!(registry.claim("TREF1", "014", "ORDER1").is_ok());
+        // Replay for another order
+        assert!(matches!(
+            registry.claim("TREF1", "014", "ORDER2"),
+            Err(SlipRegistryError::AlreadyClaimed(c)) if c.order_id == "ORDER1"
+        ));
+        // Same trans ref from another bank is another slip
+        assert!(registry.claim("TREF1", "004", "ORDER2").is_ok());
+
+        assert!(registry.release("TREF1", "014").unwrap());
+        assert!(registry.find("TREF1", "014").unwrap().is_none());
+        assert!(registry.claim("TREF1", "014", "ORDER2").is_ok());
+    }
+}
@@ -0,0 +1,435 @@
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+
+use crate::frameworks::stores::payment_store::{
+    PaymentQuery, PaymentRecord, PaymentStatus, PaymentStore, PaymentStoreError,
+};
+use crate::frameworks::stores::slip_registry::{SlipClaim, SlipRegistry, SlipRegistryError};
+
+// Time a connection waits for another process holding a lock on the database file
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Processes sharing a database file wait for each other's locks instead of failing
+// with SQLITE_BUSY, and write-ahead logging lets them read while one writes.
+// In-memory databases keep their own journal mode.
+fn configure(connection: &Connection) -> rusqlite::Result<()> {
+    connection.busy_timeout(BUSY_TIMEOUT)?;
+    connection.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))
+}
+
+// Slip registry persisted in SQLite. The primary key on (trans_ref, sending_bank)
+// keeps claims atomic even when several processes share the database file.
+#[derive(Debug)]
+pub struct SqliteSlipRegistry {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteSlipRegistry {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SlipRegistryError> {
+        let connection = Connection::open(path).map_err(storage_error)?;
+        Self::from_connection(connection)
+    }
+
+    pub fn open_in_memory() -> Result<Self, SlipRegistryError> {
+        let connection = Connection::open_in_memory().map_err(storage_error)?;
+        Self::from_connection(connection)
+    }
+
+    fn from_connection(connection: Connection) -> Result<Self, SlipRegistryError> {
+        configure(&connection).map_err(storage_error)?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS slip_claims (
+                    trans_ref TEXT NOT NULL,
+                    sending_bank TEXT NOT NULL,
+                    order_id TEXT NOT NULL,
+                    claimed_at INTEGER NOT NULL,
+                    PRIMARY KEY (trans_ref, sending_bank)
+                )",
+            )
+            .map_err(storage_error)?;
+        Ok(SqliteSlipRegistry {
+            connection: Mutex::new(connection),
+        })
+    }
+}
+
+fn storage_error<E: ToString>(error: E) -> SlipRegistryError {
+    SlipRegistryError::Storage(error.to_string())
+}
+
+fn find_claim(
+    connection: &Connection,
+    trans_ref: &str,
+    sending_bank: &str,
+) -> Result<Option<SlipClaim>, SlipRegistryError> {
+    connection
+        .query_row(
+            "SELECT trans_ref, sending_bank, order_id, claimed_at FROM slip_claims
+             WHERE trans_ref = ?1 AND sending_bank = ?2",
+            params![trans_ref, sending_bank],
+            |row| {
+                Ok(SlipClaim {
+                    trans_ref: row.get(0)?,
+                    sending_bank: row.get(1)?,
+                    order_id: row.get(2)?,
+                    claimed_at: row.get(3)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(storage_error)
+}
+
+impl SlipRegistry for SqliteSlipRegistry {
+    fn claim(
+        &self,
+        trans_ref: &str,
+        sending_bank: &str,
+        order_id: &str,
+    ) -> Result<SlipClaim, SlipRegistryError> {
+        let connection = self.connection.lock().map_err(storage_error)?;
+        connection
+            .execute(
+                "INSERT INTO slip_claims (trans_ref, sending_bank, order_id, claimed_at)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (trans_ref, sending_bank) DO NOTHING",
+                params![
+                    trans_ref,
+                    sending_bank,
+                    order_id,
+                    chrono::Utc::now().timestamp()
+                ],
+            )
+            .map_err(storage_error)?;
+
+        let claim = find_claim(&connection, trans_ref, sending_bank)?
+            .ok_or_else(|| storage_error("claim was not stored"))?;
+        if claim.order_id != order_id {
+            return Err(SlipRegistryError::AlreadyClaimed(claim));
+        }
+        Ok(claim)
+    }
+
+    fn find(
+        &self,
+        trans_ref: &str,
+        sending_bank: &str,
+    ) -> Result<Option<SlipClaim>, SlipRegistryError> {
+        let connection = self.connection.lock().map_err(storage_error)?;
+        find_claim(&connection, trans_ref, sending_bank)
+    }
+
+    fn release(&self, trans_ref: &str, sending_bank: &str) -> Result<bool, SlipRegistryError> {
+        let connection = self.connection.lock().map_err(storage_error)?;
+        let deleted = connection
+            .execute(
+                "DELETE FROM slip_claims WHERE trans_ref = ?1 AND sending_bank = ?2",
+                params![trans_ref, sending_bank],
+            )
+            .map_err(storage_error)?;
+        Ok(deleted > 0)
+    }
+}
+
+// Payment store persisted in SQLite, one row per requestUId
+#[derive(Debug)]
+pub struct SqlitePaymentStore {
+    connection: Mutex<Connection>,
+}
+
+const PAYMENT_COLUMNS: &str = "request_uid, qr_type, amount, pp_id, ref1, ref2, ref3, invoice, \
+     qr_code_id, request, response, error, status, created_at, updated_at";
+
+impl SqlitePaymentStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, PaymentStoreError> {
+        let connection = Connection::open(path).map_err(payment_storage_error)?;
+        Self::from_connection(connection)
+    }
+
+    pub fn open_in_memory() -> Result<Self, PaymentStoreError> {
+        let connection = Connection::open_in_memory().map_err(payment_storage_error)?;
+        Self::from_connection(connection)
+    }
+
+    fn from_connection(connection: Connection) -> Result<Self, PaymentStoreError> {
+        configure(&connection).map_err(payment_storage_error)?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS payments (
+                    request_uid TEXT PRIMARY KEY NOT NULL,
+                    qr_type TEXT NOT NULL,
+                    amount TEXT NOT NULL,
+                    pp_id TEXT,
+                    ref1 TEXT,
+                    ref2 TEXT,
+                    ref3 TEXT,
+                    invoice TEXT,
+                    qr_code_id TEXT,
+                    request TEXT NOT NULL,
+                    response TEXT,
+                    error TEXT,
+                    status TEXT NOT NULL,
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL
+                );",
+            )
+            .map_err(payment_storage_error)?;
+        add_pp_id_column(&connection).map_err(payment_storage_error)?;
+        connection
+            .execute_batch(
+                "CREATE INDEX IF NOT EXISTS payments_pp_id_ref1 ON payments (pp_id, ref1);
+                CREATE INDEX IF NOT EXISTS payments_ref1 ON payments (ref1);
+                CREATE INDEX IF NOT EXISTS payments_ref2 ON payments (ref2);
+                CREATE INDEX IF NOT EXISTS payments_ref3 ON payments (ref3);
+                CREATE INDEX IF NOT EXISTS payments_invoice ON payments (invoice);
+                CREATE INDEX IF NOT EXISTS payments_qr_code_id ON payments (qr_code_id);",
+            )
+            .map_err(payment_storage_error)?;
+        Ok(SqlitePaymentStore {
+            connection: Mutex::new(connection),
+        })
+    }
+}
+
+// Tables created before pp_id was stored get the column, filled from the stored requests
+fn add_pp_id_column(connection: &Connection) -> rusqlite::Result<()> {
+    let has_pp_id = connection
+        .prepare("SELECT name FROM pragma_table_info('payments') WHERE name = 'pp_id'")?
+        .exists([])?;
+    if !has_pp_id {
+        connection.execute_batch(
+            "ALTER TABLE payments ADD COLUMN pp_id TEXT;
+             UPDATE payments SET pp_id = json_extract(request, '$.ppId');",
+        )?;
+    }
+    Ok(())
+}
+
+fn payment_storage_error<E: ToString>(error: E) -> PaymentStoreError {
+    PaymentStoreError::Storage(error.to_string())
+}
+
+fn payment_from_row(row: &rusqlite::Row) -> rusqlite::Result<PaymentRecord> {
+    Ok(PaymentRecord {
+        request_uid: row.get(0)?,
+        qr_type: row.get(1)?,
+        amount: row.get(2)?,
+        pp_id: row.get(3)?,
+        ref1: row.get(4)?,
+        ref2: row.get(5)?,
+        ref3: row.get(6)?,
+        invoice: row.get(7)?,
+        qr_code_id: row.get(8)?,
+        request: row.get(9)?,
+        response: row.get(10)?,
+        error: row.get(11)?,
+        status: PaymentStatus::from(row.get::<_, String>(12)?),
+        created_at: row.get(13)?,
+        updated_at: row.get(14)?,
+    })
+}
+
+impl PaymentStore for SqlitePaymentStore {
+    fn save(&self, record: &PaymentRecord) -> Result<(), PaymentStoreError> {
+        let connection = self.connection.lock().map_err(payment_storage_error)?;
+        connection
+            .execute(
+                &format!(
+                    "INSERT OR REPLACE INTO payments ({})
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+                    PAYMENT_COLUMNS
+                ),
+                params![
+                    record.request_uid,
+                    record.qr_type,
+                    record.amount,
+                    record.pp_id,
+                    record.ref1,
+                    record.ref2,
+                    record.ref3,
+                    record.invoice,
+                    record.qr_code_id,
+                    record.request,
+                    record.response,
+                    record.error,
+                    record.status.to_string(),
+                    record.created_at,
+                    record.updated_at
+                ],
+            )
+            .map_err(payment_storage_error)?;
+        Ok(())
+    }
+
+    fn find(&self, query: &PaymentQuery) -> Result<Vec<PaymentRecord>, PaymentStoreError> {
+        let (condition, values) = match query {
+            PaymentQuery::RequestUId(v) => ("request_uid = ?1", vec![v]),
+            PaymentQuery::Ref1(v) => ("ref1 = ?1", vec![v]),
+            PaymentQuery::Ref2(v) => ("ref2 = ?1", vec![v]),
+            PaymentQuery::Ref3(v) => ("ref3 = ?1", vec![v]),
+            PaymentQuery::Invoice(v) => ("invoice = ?1", vec![v]),
+            PaymentQuery::QRCodeId(v) => ("qr_code_id = ?1", vec![v]),
+            PaymentQuery::Biller { pp_id, ref1 } => ("pp_id = ?1 AND ref1 = ?2", vec![pp_id, ref1]),
+        };
+        let connection = self.connection.lock().map_err(payment_storage_error)?;
+        let mut statement = connection
+            .prepare(&format!(
+                "SELECT {} FROM payments WHERE {} ORDER BY created_at, request_uid",
+                PAYMENT_COLUMNS, condition
+            ))
+            .map_err(payment_storage_error)?;
+        let records = statement
+            .query_map(params_from_iter(values), payment_from_row)
+            .map_err(payment_storage_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(payment_storage_error)?;
+        Ok(records)
+    }
+
+    fn update_status(
+        &self,
+        request_uid: &str,
+        status: PaymentStatus,
+    ) -> Result<PaymentRecord, PaymentStoreError> {
+        let connection = self.connection.lock().map_err(payment_storage_error)?;
+        connection
+            .execute(
+                "UPDATE payments SET status = ?2, updated_at = ?3 WHERE request_uid = ?1",
+                params![
+                    request_uid,
+                    status.to_string(),
+                    chrono::Utc::now().timestamp()
+                ],
+            )
+            .map_err(payment_storage_error)?;
+        connection
+            .query_row(
+                &format!(
+                    "SELECT {} FROM payments WHERE request_uid = ?1",
+                    PAYMENT_COLUMNS
+                ),
+                params![request_uid],
+                payment_from_row,
+            )
+            .optional()
+            .map_err(payment_storage_error)?
+            .ok_or_else(|| PaymentStoreError::NotFound(request_uid.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frameworks::stores::payment_store;
+
+    #[test]
+    fn test_sqlite_claim() {
+        let registry = SqliteSlipRegistry::open_in_memory().unwrap();
+        registry.claim("TREF1", "014", "ORDER1").unwrap();
+        assert!(registry.claim("TREF1", "014", "ORDER1").is_ok());
+        assert!(matches!(
+            registry.claim("TREF1", "014", "ORDER2"),
+            Err(SlipRegistryError::AlreadyClaimed(c)) if c.order_id == "ORDER1"
+        ));
+        assert!(registry.release("TREF1", "014").unwrap());
+        assert!(!registry.release("TREF1", "014").unwrap());
+    }
+
+    #[test]
+    fn test_sqlite_shared_file() {
+        let path = std::env::temp_dir().join(format!("scb-slips-{}.db", std::process::id()));
+        let registry = SqliteSlipRegistry::open(&path).unwrap();
+        let other = SqliteSlipRegistry::open(&path).unwrap();
+        registry.claim("TREF1", "014", "ORDER1").unwrap();
+        assert!(matches!(
+            other.claim("TREF1", "014", "ORDER2"),
+            Err(SlipRegistryError::AlreadyClaimed(_))
+        ));
+        let connection = other.connection.lock().unwrap();
+        let journal_mode: String = connection
+            .pragma_query_value(None, "journal_mode", |row| row.get(0))
+            .unwrap();
+        assert_eq!(journal_mode, "wal");
+        drop(connection);
+        drop((registry, other));
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    #[test]
+    fn test_sqlite_payment_store() {
+        let store = SqlitePaymentStore::open_in_memory().unwrap();
+        let mut first = payment_store::tests::record("UID1", "ORDER1", 1);
+        first.qr_code_id = Some("QR1".to_string());
+        store.save(&first).unwrap();
+        store
+            .save(&payment_store::tests::record("UID2", "ORDER2", 2))
+            .unwrap();
+
+        let found = store
+            .find(&PaymentQuery::QRCodeId("QR1".to_string()))
+            .unwrap();
+        assert_eq!(found, vec![first]);
+        assert_eq!(
+            store
+                .find(&PaymentQuery::Ref3("SCB1".to_string()))
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let biller = PaymentQuery::Biller {
+            pp_id: "123456789012345".to_string(),
+            ref1: "ORDER2".to_string(),
+        };
+        assert_eq!(store.find(&biller).unwrap().len(), 1);
+
+        let updated = store.update_status("UID2", PaymentStatus::Expired).unwrap();
+        assert_eq!(updated.status, PaymentStatus::Expired);
+        assert!(matches!(
+            store.update_status("UID9", PaymentStatus::Paid),
+            Err(PaymentStoreError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_sqlite_payment_store_adds_pp_id() {
+        let connection = Connection::open_in_memory().unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE payments (
+                    request_uid TEXT PRIMARY KEY NOT NULL,
+                    qr_type TEXT NOT NULL,
+                    amount TEXT NOT NULL,
+                    ref1 TEXT,
+                    ref2 TEXT,
+                    ref3 TEXT,
+                    invoice TEXT,
+                    qr_code_id TEXT,
+                    request TEXT NOT NULL,
+                    response TEXT,
+                    error TEXT,
+                    status TEXT NOT NULL,
+                    created_at INTEGER NOT NULL,
+                    updated_at INTEGER NOT NULL
+                );
+                INSERT INTO payments VALUES ('UID1', 'PP', '100.00', 'ORDER1', NULL, NULL, NULL,
+                    NULL, '{\"ppId\":\"123456789012345\"}', NULL, NULL, 'CREATED', 1, 1);",
+            )
+            .unwrap();
+        let store = SqlitePaymentStore::from_connection(connection).unwrap();
+        let found = store
+            .find(&PaymentQuery::Biller {
+                pp_id: "123456789012345".to_string(),
+                ref1: "ORDER1".to_string(),
+            })
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].pp_id.as_deref(), Some("123456789012345"));
+    }
+}
@@ -1,5 +1,5 @@
 pub mod entities;
-mod errors;
+pub mod errors;
 pub mod frameworks;
 
 pub fn add(left: usize, right: usize) -> usize {
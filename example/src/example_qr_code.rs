@@ -2,6 +2,7 @@ use image::Luma;
 use log::{debug, error, info};
 use qrcode::QrCode;
 
+use corescbsdk::entities::proxy::ProxyType;
 use corescbsdk::entities::qr_code::{QRCodeRequestBuilder, QRCodeType};
 use corescbsdk::frameworks::apis::scb::SCBClientAPI;
 
@@ -47,7 +48,7 @@ async fn generate_qr_code(
     let mut qr_code_req_builder = QRCodeRequestBuilder::new(&QRCodeType::PP, &"100.00".to_string());
     let qr_code_req_builder = qr_code_req_builder
         .for_qr_tag30(
-            &"BILLERID".to_string(),
+            &ProxyType::BillerId,
             &biller_id,
             &"REFERENCE1".to_string(),
             &ref3,
@@ -1,5 +1,6 @@
 use log::info;
 
+use corescbsdk::entities::bank_code::BankCode;
 use corescbsdk::entities::bill_pay::BillPaymentInquiryRequest;
 use corescbsdk::frameworks::apis::scb::SCBClientAPI;
 
@@ -15,7 +16,7 @@ async fn main() {
 
     let mut scb_client = SCBClientAPI::new(&application_name, &application_key, &secret_key);
     let r = scb_client
-        .get_slip_verification_qr30(&"12345".to_string(), &"014".to_string())
+        .get_slip_verification_qr30(&"12345".to_string(), &BankCode::SCB)
         .await;
 
     match r {
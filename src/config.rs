@@ -0,0 +1,41 @@
+use std::path::{Path, PathBuf};
+
+use corescbsdk::frameworks::config::{SCBConfig, SCBConfigBuilder, SCBConfigError};
+
+use crate::error::CliError;
+
+// ~/.config/scb/config.toml, profiles as in corescbsdk::frameworks::config::SCBConfigFile
+pub fn default_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config/scb/config.toml"))
+}
+
+// Profile of the config file, values missing there are read from APP_NAME, APP_KEY,
+// APP_SECRET, BILLER_ID and REF_3PREFIX
+pub fn load(path: Option<&Path>, profile: Option<&str>) -> Result<SCBConfig, CliError> {
+    let mut builder = SCBConfigBuilder::new();
+    builder.add_env();
+    let path = path
+        .map(Path::to_path_buf)
+        .or_else(|| default_path().filter(|p| p.exists()));
+    match (path, profile) {
+        (Some(path), _) => {
+            builder.add_file(path, profile)?;
+        }
+        (None, Some(profile)) => {
+            return Err(SCBConfigError::ProfileNotFound(profile.to_string()).into())
+        }
+        (None, None) => {}
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_config_file() {
+        let result = load(Some(Path::new("/nonexistent/config.toml")), None);
+        assert!(matches!(result, Err(CliError::Config(e)) if e.contains("/nonexistent")));
+    }
+}
@@ -0,0 +1,84 @@
+use std::fmt;
+use std::process::ExitCode;
+
+use corescbsdk::errors::scb_error::SCBAPIError;
+use corescbsdk::frameworks::config::SCBConfigError;
+
+#[derive(Debug)]
+pub enum CliError {
+    // Missing or invalid config file, profile or credentials
+    Config(String),
+    // Request rejected before calling SCB
+    InvalidRequest(String),
+    Io(std::io::Error),
+    Api(SCBAPIError),
+}
+
+impl CliError {
+    // 2 is used by clap for usage errors
+    pub fn exit_code(&self) -> ExitCode {
+        let code = match self {
+            CliError::Config(_) => 3,
+            CliError::InvalidRequest(_) => 4,
+            CliError::Io(_) => 5,
+            CliError::Api(e) => match e {
+                SCBAPIError::HttpRequestError(_) => 10,
+                SCBAPIError::SCBError(_) => 11,
+                SCBAPIError::AuthorizationError(_) => 12,
+                SCBAPIError::DuplicateSlip { .. } => 13,
+                SCBAPIError::StorageError(_) => 14,
+                SCBAPIError::RateLimited { .. } => 15,
+                SCBAPIError::CircuitOpen { .. } => 16,
+                SCBAPIError::ServerError { .. } => 17,
+                SCBAPIError::InvalidResponse(_) => 18,
+            },
+        };
+        ExitCode::from(code)
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Config(e) => write!(f, "Config Error: {}", e),
+            CliError::InvalidRequest(e) => write!(f, "Invalid Request: {}", e),
+            CliError::Io(e) => write!(f, "IO Error: {}", e),
+            CliError::Api(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<SCBAPIError> for CliError {
+    fn from(error: SCBAPIError) -> Self {
+        CliError::Api(error)
+    }
+}
+
+impl From<SCBConfigError> for CliError {
+    fn from(error: SCBConfigError) -> Self {
+        CliError::Config(error.to_string())
+    }
+}
+
+impl From<std::io::Error> for CliError {
+    fn from(error: std::io::Error) -> Self {
+        CliError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(
+            CliError::Config("missing".to_string()).exit_code(),
+            ExitCode::from(3)
+        );
+        assert_eq!(
+            CliError::from(SCBAPIError::AuthorizationError("expired".to_string())).exit_code(),
+            ExitCode::from(12)
+        );
+    }
+}
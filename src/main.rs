@@ -1,3 +1,368 @@
-fn main() {
-    println!("Hello, world!");
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use image::Luma;
+use qrcode::render::unicode;
+use qrcode::QrCode;
+use validator::Validate;
+
+use corescbsdk::entities::bank_code::BankCode;
+use corescbsdk::entities::bill_pay::{BillPaymentInquiryRequest, EVENT_CODE_QR30_C_SCAN_B};
+use corescbsdk::entities::proxy::ProxyType;
+use corescbsdk::entities::qr_code::{QRCodeRequestBuilder, QRCodeType};
+use corescbsdk::errors::scb_error::SCBAPIError;
+use corescbsdk::frameworks::apis::blocking::SCBClientAPI;
+use corescbsdk::frameworks::stores::sqlite::SqliteSlipRegistry;
+
+use crate::error::CliError;
+use crate::output::Format;
+
+mod config;
+mod error;
+mod output;
+
+const INQUIRY_COLUMNS: &[&str] = &[
+    "transactionId",
+    "transactionDateandTime",
+    "amount",
+    "billPaymentRef1",
+    "billPaymentRef2",
+    "billPaymentRef3",
+    "payerName",
+    "sendingBankCode",
+];
+
+#[derive(Parser)]
+#[command(
+    name = "scb",
+    about = "SCB Open API from the command line",
+    after_help = "Exit codes: 2 usage, 3 config, 4 invalid request, 5 IO, 10 HTTP request, \
+                  11 SCB error, 12 authorization, 13 duplicate slip, 14 storage, \
+                  15 rate limited, 16 circuit open, 17 server error, 18 invalid response"
+)]
+struct Cli {
+    /// Config file with profiles, defaults to ~/.config/scb/config.toml
+    #[arg(long, global = true, env = "SCB_CONFIG")]
+    config: Option<PathBuf>,
+    /// Profile of the config file, defaults to its default_profile
+    #[arg(long, short, global = true, env = "SCB_PROFILE")]
+    profile: Option<String>,
+    #[arg(long, short, global = true, value_enum, default_value_t = Format::Table)]
+    format: Format,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Request an application access token
+    Token,
+    /// QR code payments
+    Qr {
+        #[command(subcommand)]
+        command: QrCommand,
+    },
+    /// Slip verification
+    Slip {
+        #[command(subcommand)]
+        command: SlipCommand,
+    },
+    /// Bill payment inquiry of a day
+    Inquiry(InquiryArgs),
+}
+
+#[derive(Subcommand)]
+enum QrCommand {
+    /// Create a QR 30 and/or QR CS
+    Create(QrCreateArgs),
+}
+
+#[derive(Subcommand)]
+enum SlipCommand {
+    /// Verify a slip by its transaction reference
+    Verify(SlipVerifyArgs),
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum QrType {
+    Pp,
+    Cs,
+    Ppcs,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Render {
+    /// Response data in the selected format
+    Raw,
+    /// QR image saved to --out
+    Png,
+    /// QR drawn in the terminal
+    Terminal,
+}
+
+#[derive(Args)]
+struct QrCreateArgs {
+    #[arg(long = "type", value_enum)]
+    qr_type: QrType,
+    #[arg(long)]
+    amount: String,
+    /// QR 30 biller id, defaults to the biller id of the profile
+    #[arg(long)]
+    biller_id: Option<String>,
+    #[arg(long)]
+    ref1: Option<String>,
+    #[arg(long)]
+    ref2: Option<String>,
+    /// QR 30, generated with the ref3 prefix of the profile when not given
+    #[arg(long)]
+    ref3: Option<String>,
+    /// QR CS invoice number
+    #[arg(long)]
+    invoice: Option<String>,
+    #[arg(long)]
+    merchant_id: Option<String>,
+    #[arg(long)]
+    terminal_id: Option<String>,
+    #[arg(long, value_enum, default_value_t = Render::Raw)]
+    render: Render,
+    #[arg(long, default_value = "qrcode.png")]
+    out: PathBuf,
+}
+
+#[derive(Args)]
+struct SlipVerifyArgs {
+    trans_ref: String,
+    /// 3 digit code of the sending bank, e.g. 014
+    #[arg(long)]
+    bank: String,
+    /// Claim the slip for an order in the slip registry, a reused slip exits with 13
+    #[arg(long)]
+    order_id: Option<String>,
+    /// SQLite database of the slips claimed for orders, created when missing
+    #[arg(long, env = "SCB_SLIP_REGISTRY")]
+    registry: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct InquiryArgs {
+    /// Transaction date, yyyy-MM-dd
+    #[arg(long)]
+    date: String,
+    /// Biller id, defaults to the biller id of the profile
+    #[arg(long)]
+    biller: Option<String>,
+    /// Reference 1, required for C scan B
+    #[arg(long)]
+    ref1: Option<String>,
+    #[arg(long)]
+    ref2: Option<String>,
+    #[arg(long, default_value = EVENT_CODE_QR30_C_SCAN_B)]
+    event_code: String,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            e.exit_code()
+        }
+    }
+}
+
+fn run(cli: &Cli) -> Result<(), CliError> {
+    let config = config::load(cli.config.as_deref(), cli.profile.as_deref())?;
+    let mut client = SCBClientAPI::from_config(&config);
+    match &cli.command {
+        Command::Token => output::print(&client.get_access_token()?, cli.format, &[]),
+        Command::Qr {
+            command: QrCommand::Create(args),
+        } => qr_create(&mut client, args, cli.format),
+        Command::Slip {
+            command: SlipCommand::Verify(args),
+        } => slip_verify(&mut client, args, cli.format),
+        Command::Inquiry(args) => {
+            let request = BillPaymentInquiryRequest {
+                event_code: args.event_code.clone(),
+                transaction_date: args.date.clone(),
+                biller_id: args
+                    .biller
+                    .clone()
+                    .or(client.biller_id().map(|s| s.to_string())),
+                reference1: args.ref1.clone(),
+                reference2: args.ref2.clone(),
+                partner_transaction_id: None,
+                amount: None,
+            };
+            request
+                .validate()
+                .map_err(|e| CliError::InvalidRequest(e.to_string()))?;
+            let transactions = client.query_bill_payment_transaction(&request)?;
+            output::print(&transactions, cli.format, INQUIRY_COLUMNS)
+        }
+    }
+}
+
+fn slip_verify(
+    client: &mut SCBClientAPI,
+    args: &SlipVerifyArgs,
+    format: Format,
+) -> Result<(), CliError> {
+    let bank = BankCode::from_code(&args.bank);
+    let slip = match &args.order_id {
+        Some(order_id) => {
+            let path = args.registry.as_ref().ok_or_else(|| {
+                CliError::InvalidRequest(
+                    "--order-id requires --registry or SCB_SLIP_REGISTRY".to_string(),
+                )
+            })?;
+            let registry = SqliteSlipRegistry::open(path).map_err(SCBAPIError::from)?;
+            client.set_slip_registry(Arc::new(registry));
+            client.get_slip_verification_for_order(&args.trans_ref, &bank, order_id)?
+        }
+        None => client.get_slip_verification_qr30(&args.trans_ref, &bank)?,
+    };
+    output::print(&slip, format, &[])
+}
+
+fn qr_create(
+    client: &mut SCBClientAPI,
+    args: &QrCreateArgs,
+    format: Format,
+) -> Result<(), CliError> {
+    let invalid = |message: &str| CliError::InvalidRequest(message.to_string());
+    let qr_type = match args.qr_type {
+        QrType::Pp => QRCodeType::PP,
+        QrType::Cs => QRCodeType::CS,
+        QrType::Ppcs => QRCodeType::PPCS,
+    };
+    let mut builder = QRCodeRequestBuilder::new(&qr_type, &args.amount);
+    if matches!(args.qr_type, QrType::Pp | QrType::Ppcs) {
+        let biller_id = args
+            .biller_id
+            .clone()
+            .or(client.biller_id().map(|s| s.to_string()))
+            .ok_or_else(|| invalid("--biller-id or BILLER_ID is required for QR 30"))?;
+        let ref1 = args
+            .ref1
+            .clone()
+            .ok_or_else(|| invalid("--ref1 is required for QR 30"))?;
+        let ref3 = match &args.ref3 {
+            Some(ref3) => ref3.clone(),
+            None => client
+                .ref3_generator()
+                .map_err(|_| {
+                    invalid("--ref3 or a ref3 prefix (REF_3PREFIX) is required for QR 30")
+                })?
+                .generate(),
+        };
+        builder.for_qr_tag30(&ProxyType::BillerId, &biller_id, &ref1, &ref3);
+        if let Some(ref2) = &args.ref2 {
+            builder.add_ref2(ref2);
+        }
+    }
+    if matches!(args.qr_type, QrType::Cs | QrType::Ppcs) {
+        match (&args.invoice, &args.merchant_id, &args.terminal_id) {
+            (Some(invoice), Some(merchant_id), Some(terminal_id)) => {
+                builder.for_qr_cs(invoice, merchant_id, terminal_id);
+            }
+            _ => {
+                return Err(invalid(
+                    "--invoice, --merchant-id and --terminal-id are required for QR CS",
+                ))
+            }
+        }
+    }
+    let request = builder.build().map_err(invalid)?;
+    request
+        .validate()
+        .map_err(|e| CliError::InvalidRequest(e.to_string()))?;
+
+    let response = client.qr_code_create(&request)?;
+    if args.render == Render::Raw {
+        return output::print(&response, format, &[]);
+    }
+    let raw_data = response
+        .qr_raw_data
+        .ok_or_else(|| CliError::Api("SCB returned no QR raw data".to_string().into()))?;
+    let code = QrCode::new(raw_data.as_bytes())
+        .map_err(|e| CliError::InvalidRequest(format!("QR raw data does not fit: {}", e)))?;
+    match args.render {
+        Render::Png => {
+            code.render::<Luma<u8>>()
+                .build()
+                .save(&args.out)
+                .map_err(|e| CliError::Io(std::io::Error::other(e)))?;
+            println!("{}", args.out.display());
+        }
+        _ => println!(
+            "{}",
+            code.render::<unicode::Dense1x2>().quiet_zone(true).build()
+        ),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client() -> SCBClientAPI {
+        SCBClientAPI::new("app", "key", "secret")
+    }
+
+    fn command(args: &[&str]) -> Command {
+        Cli::try_parse_from([&["scb"], args].concat())
+            .unwrap()
+            .command
+    }
+
+    #[test]
+    fn test_qr_create_requires_ref3() {
+        let Command::Qr {
+            command: QrCommand::Create(args),
+        } = command(&[
+            "qr",
+            "create",
+            "--type",
+            "pp",
+            "--amount",
+            "100.00",
+            "--biller-id",
+            "311040039475180",
+            "--ref1",
+            "ORDER1",
+        ])
+        else {
+            unreachable!()
+        };
+        let error = qr_create(&mut client(), &args, Format::Json).unwrap_err();
+        assert!(error.to_string().contains("--ref3"));
+        assert_eq!(error.exit_code(), ExitCode::from(4));
+    }
+
+    #[test]
+    fn test_slip_order_requires_registry() {
+        std::env::remove_var("SCB_SLIP_REGISTRY");
+        let Command::Slip {
+            command: SlipCommand::Verify(args),
+        } = command(&[
+            "slip",
+            "verify",
+            "REF",
+            "--bank",
+            "014",
+            "--order-id",
+            "ORDER1",
+        ])
+        else {
+            unreachable!()
+        };
+        let error = slip_verify(&mut client(), &args, Format::Json).unwrap_err();
+        assert!(error.to_string().contains("--registry"));
+        assert_eq!(error.exit_code(), ExitCode::from(4));
+    }
 }
@@ -0,0 +1,154 @@
+use std::io::Write;
+
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::CliError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Json,
+    Table,
+}
+
+// Objects are printed as field/value rows with nested fields joined by ".",
+// lists of objects as one row per item with `columns`, or every field when empty
+pub fn print<T: Serialize>(value: &T, format: Format, columns: &[&str]) -> Result<(), CliError> {
+    let value = serde_json::to_value(value).map_err(|e| CliError::InvalidRequest(e.to_string()))?;
+    let mut out = std::io::stdout().lock();
+    match format {
+        Format::Json => {
+            let json = serde_json::to_string_pretty(&value)
+                .map_err(|e| CliError::InvalidRequest(e.to_string()))?;
+            writeln!(out, "{}", json)?;
+        }
+        Format::Table => write!(out, "{}", render_table(&value, columns))?,
+    }
+    Ok(())
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn flatten(prefix: &str, value: &Value, rows: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(&key, value, rows);
+            }
+        }
+        Value::Null => {}
+        other => rows.push((prefix.to_string(), scalar(other))),
+    }
+}
+
+fn format_rows(header: &[String], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: &[String]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+    let mut table = line(header);
+    let separator: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+    table.push_str(&line(&separator));
+    for row in rows {
+        table.push_str(&line(row));
+    }
+    table
+}
+
+fn render_table(value: &Value, columns: &[&str]) -> String {
+    match value {
+        Value::Array(items) => {
+            let flattened: Vec<Vec<(String, String)>> = items
+                .iter()
+                .map(|item| {
+                    let mut rows = Vec::new();
+                    flatten("", item, &mut rows);
+                    rows
+                })
+                .collect();
+            let header: Vec<String> = if columns.is_empty() {
+                let mut header: Vec<String> = Vec::new();
+                for (key, _) in flattened.iter().flatten() {
+                    if !header.contains(key) {
+                        header.push(key.clone());
+                    }
+                }
+                header
+            } else {
+                columns.iter().map(|c| c.to_string()).collect()
+            };
+            let rows: Vec<Vec<String>> = flattened
+                .iter()
+                .map(|item| {
+                    header
+                        .iter()
+                        .map(|column| {
+                            item.iter()
+                                .find(|(key, _)| key == column)
+                                .map(|(_, value)| value.clone())
+                                .unwrap_or_default()
+                        })
+                        .collect()
+                })
+                .collect();
+            format_rows(&header, &rows)
+        }
+        Value::Object(_) => {
+            let mut rows = Vec::new();
+            flatten("", value, &mut rows);
+            let rows: Vec<Vec<String>> = rows.into_iter().map(|(k, v)| vec![k, v]).collect();
+            format_rows(&["field".to_string(), "value".to_string()], &rows)
+        }
+        other => format!("{}\n", scalar(other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_render_object() {
+        let value = json!({"sender": {"name": "A"}, "transRef": "REF1", "ref2": null});
+        assert_eq!(
+            render_table(&value, &[]),
+            "field        value\n-----------  -----\nsender.name  A\ntransRef     REF1\n"
+        );
+    }
+
+    #[test]
+    fn test_render_list() {
+        let value = json!([
+            {"transactionId": "T1", "amount": "100.00", "payerName": "A"},
+            {"transactionId": "T22", "amount": "5.00"}
+        ]);
+        assert_eq!(
+            render_table(&value, &["transactionId", "amount"]),
+            "transactionId  amount\n-------------  ------\nT1             100.00\nT22            5.00\n"
+        );
+    }
+}